@@ -0,0 +1,1348 @@
+use std::{
+    iter,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use indexmap::IndexMap;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use once_cell::sync::Lazy;
+use percent_encoding;
+use regex::Regex;
+use reqwest::{
+    header::{self, HeaderMap},
+    Client, RequestBuilder, Response, StatusCode, Url,
+};
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::error::Error;
+
+/// How to retry requests which failed transiently
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// The maximum number of retries after the first attempt
+    pub retries: usize,
+    /// The delay before the first retry, which is doubled on each retry
+    pub delay: Duration,
+    /// The longest wait which `Retry-After` of 429 responses can ask for
+    pub max_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            delay: Duration::from_secs(1),
+            max_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Wait which the `Retry-After` header asks for, in either seconds or an HTTP-date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    // e.g. "Wed, 21 Oct 2015 07:28:00 GMT", which is a date of RFC 2822 as well
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past asks for no wait
+    Some(
+        date.signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Send the request built by `request`, and retry it with exponential backoff on network errors,
+/// 429 and 5xx responses
+///
+/// 429 responses are retried after the wait of their `Retry-After` header instead,
+/// which is capped by `max_wait` of the policy.
+/// If the last attempt is answered with 429, `Error::RateLimit` is returned,
+/// and with another transient status code, the response is returned as is.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() -> Result<(), atcoder4rust::Error> {
+/// use atcoder4rust::RetryPolicy;
+/// use reqwest::Client;
+///
+/// let client = Client::new();
+/// let response = atcoder4rust::send_with_retry(
+///     || client.get("https://atcoder.jp/"),
+///     &RetryPolicy::default(),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn send_with_retry<F>(request: F, policy: &RetryPolicy) -> Result<Response, Error>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut delay = policy.delay;
+    let mut retries = 0;
+    loop {
+        let result = request().send().await;
+        if let Ok(response) = &result {
+            debug!(url = %response.url(), status = %response.status(), "response");
+        }
+        let wait = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                retry_after(response).map(|wait| wait.min(policy.max_wait))
+            }
+            _ => None,
+        };
+        match result {
+            Ok(response)
+                if retries >= policy.retries
+                    && response.status() == StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Err(Error::RateLimit(wait.unwrap_or(delay)))
+            }
+            Ok(response) if retries >= policy.retries || !is_transient(response.status()) => {
+                return Ok(response)
+            }
+            Err(error) if retries >= policy.retries || error.is_builder() => {
+                return Err(Error::Network {
+                    retries,
+                    last: error,
+                })
+            }
+            Ok(response) => warn!(
+                "{} responded {}; retrying in {:?}",
+                response.url(),
+                response.status(),
+                wait.unwrap_or(delay)
+            ),
+            Err(error) => warn!("{}; retrying in {:?}", error, delay),
+        }
+        tokio::time::delay_for(wait.unwrap_or(delay)).await;
+        delay *= 2;
+        retries += 1;
+    }
+}
+
+/// Extract the csrf token from the `REVEL_SESSION` cookie in the values of `Set-Cookie` headers
+///
+/// If the session is set more than once, the last one wins as well as in browsers.
+///
+/// # Examples
+/// ```
+/// let headers = [
+///     "REVEL_FLASH=; Path=/",
+///     "REVEL_SESSION=a%00csrf_token%3AAbC%2B%2F%3D%00b; Path=/; HttpOnly",
+/// ];
+/// assert_eq!(atcoder4rust::get_csrf_token(&headers).unwrap(), "AbC+/=");
+/// assert!(atcoder4rust::get_csrf_token(&["REVEL_FLASH=; Path=/"]).is_err());
+///
+/// // The last session wins
+/// let headers = [
+///     "REVEL_SESSION=csrf_token%3Aold; Path=/",
+///     "REVEL_SESSION=csrf_token%3Anew; Path=/",
+/// ];
+/// assert_eq!(atcoder4rust::get_csrf_token(&headers).unwrap(), "new");
+///
+/// // A session without the token, or with an empty one, is an error
+/// assert!(atcoder4rust::get_csrf_token(&["REVEL_SESSION=a%00b; Path=/"]).is_err());
+/// assert!(atcoder4rust::get_csrf_token(&["REVEL_SESSION=csrf_token%3A; Path=/"]).is_err());
+/// ```
+pub fn get_csrf_token(cookie_headers: &[&str]) -> Result<String, Error> {
+    let not_found = || Error::Auth("CSRF token not found".to_string());
+    let session = cookie_headers
+        .iter()
+        .rev()
+        .map(|value| value.trim_start())
+        .find(|value| value.starts_with("REVEL_SESSION="))
+        .ok_or_else(not_found)?;
+    let session = percent_encoding::percent_decode_str(session).decode_utf8()?;
+    // Fields of the session are separated by NUL, and attributes of the cookie by semicolons
+    let token = session
+        .split(|c| c == ';' || c == '\0')
+        .map(str::trim)
+        .find_map(|field| field.strip_prefix("csrf_token"))
+        .ok_or_else(not_found)?;
+    match token.strip_prefix(':') {
+        Some(token) if !token.is_empty() => Ok(token.to_string()),
+        _ => Err(Error::Auth(format!("Malformed CSRF token: {}", token))),
+    }
+}
+
+/// Values of the `Set-Cookie` headers of the response
+fn set_cookie_headers(response: &Response) -> Vec<&str> {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect()
+}
+
+/// Collect the cookies which the response sets as `Cookie` headers
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() -> Result<(), atcoder4rust::Error> {
+/// let response = reqwest::get("https://atcoder.jp/").await?;
+/// let cookies = atcoder4rust::get_cookies(&response);
+/// # Ok(())
+/// # }
+/// ```
+pub fn get_cookies(response: &Response) -> HeaderMap {
+    let mut cookies = HeaderMap::new();
+    // `append` keeps every cookie under the same `Cookie` header name
+    for value in response
+        .cookies()
+        .filter_map(|cookie| format!("{}={}", cookie.name(), cookie.value()).parse().ok())
+    {
+        cookies.append(header::COOKIE, value);
+    }
+    cookies
+}
+
+/// Labels of the sample headers in Japanese pages: (input, output)
+const JAPANESE_LABELS: (&str, &str) = ("入力例", "出力例");
+/// Labels of the sample headers in English pages: (input, output)
+const ENGLISH_LABELS: (&str, &str) = ("Sample Input", "Sample Output");
+
+/// Selectors of the elements which wrap each sample, from the oldest layout to the newest one
+const PART_SELECTORS: [&str; 3] = [
+    "#task-statement .part",
+    "#task-statement .io-style",
+    "#task-statement section",
+];
+
+/// Parse the CSS selector, whose error is converted to a String because it borrows the input
+fn selector(selectors: &str) -> Result<Selector, Error> {
+    Selector::parse(selectors).map_err(|error| Error::Parse(format!("{:?}", error)))
+}
+
+/// Parse the CSS selector which is a literal in this file, and hence known to be valid
+fn known_selector(selectors: &'static str) -> Selector {
+    selector(selectors).expect("selector literal is invalid")
+}
+
+// The selectors and regexes below are constant, so they panic only if a literal is mistyped,
+// which every test parsing a page catches, instead of returning an error for the input.
+// Selectors matched against every task page are parsed only once and shared by the pages fetched
+// at the same time, rather than parsed again for each page
+static H3: Lazy<Selector> = Lazy::new(|| known_selector("h3"));
+static PRE: Lazy<Selector> = Lazy::new(|| known_selector("pre"));
+static TD: Lazy<Selector> = Lazy::new(|| known_selector("td"));
+static PARTS: Lazy<Vec<(&str, Selector)>> = Lazy::new(|| {
+    PART_SELECTORS
+        .iter()
+        .map(|selectors| (*selectors, known_selector(selectors)))
+        .collect()
+});
+static SECTION_PRE: Lazy<Selector> = Lazy::new(|| known_selector("#task-statement section > pre"));
+static TH: Lazy<Selector> = Lazy::new(|| known_selector("th"));
+static TABLE_ROWS: Lazy<Selector> = Lazy::new(|| known_selector("table tr"));
+static LEGACY_SAMPLE_ROWS: Lazy<Selector> =
+    Lazy::new(|| known_selector("#task-statement .io-style table tr"));
+static TASK_STATEMENT: Lazy<Selector> = Lazy::new(|| known_selector("#task-statement"));
+static TASK_TITLE: Lazy<Selector> = Lazy::new(|| known_selector("#task-statement h2"));
+static TIME_LIMIT: Lazy<Selector> = Lazy::new(|| known_selector(".time-limit"));
+static MEMORY_LIMIT: Lazy<Selector> = Lazy::new(|| known_selector(".memory-limit"));
+/// `src` of `<img>` in serialized HTML
+static IMG_SRC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(<img\b[^>]*?\bsrc=")([^"]*)""#).expect("regex literal is invalid"));
+/// "配点 : 300 点" or "Score : 300 points" in the statement
+static SCORE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:配点|Score)\s*[:：]\s*(\d+)").expect("regex literal is invalid"));
+
+/// Text of the sample in the `pre` element
+///
+/// Text nodes are already unescaped (e.g. `&lt;` is `<`) and free of nested tags
+/// such as highlighting spans, unlike inner_html().
+/// CRLF is normalized to LF, or tests compare it with the LF output of the solution.
+fn sample_text(pre: ElementRef) -> String {
+    pre.text().collect::<String>().replace("\r\n", "\n")
+}
+
+fn parse_samples_with(
+    document: &Html,
+    (part_name, part_selector): &(&str, Selector),
+    (input_label, output_label): (&str, &str),
+) -> (Vec<String>, Vec<String>) {
+    let (inputs, outputs): (Vec<_>, Vec<_>) = document
+        .select(part_selector)
+        .filter_map(|part| {
+            part.select(&H3)
+                .filter_map(|h3| {
+                    if let Some(text) = h3.text().find(|text| text.starts_with(input_label)) {
+                        text[input_label.len()..]
+                            .split_whitespace()
+                            .next()
+                            .and_then(|index| Some((part, index, true)))
+                    } else if let Some(text) = h3.text().find(|text| text.starts_with(output_label))
+                    {
+                        text[output_label.len()..]
+                            .split_whitespace()
+                            .next()
+                            .and_then(|index| Some((part, index, false)))
+                    } else {
+                        None
+                    }
+                })
+                .next()
+        })
+        .filter_map(|(part, index, is_input)| {
+            part.select(&PRE)
+                .map(|pre| (sample_text(pre), index, is_input))
+                .next()
+        })
+        .partition(|(_, _, is_input)| *is_input);
+    debug!(
+        selector = part_name,
+        label = input_label,
+        inputs = inputs.len(),
+        outputs = outputs.len(),
+        "matched samples"
+    );
+    (
+        inputs.into_iter().map(|(input, _, _)| input).collect(),
+        outputs.into_iter().map(|(output, _, _)| output).collect(),
+    )
+}
+
+/// Parse samples in `section > pre` whose sections have no headings,
+/// taking the `pre`s as inputs and outputs alternately
+fn parse_bare_section_samples(document: &Html) -> (Vec<String>, Vec<String>) {
+    let (inputs, outputs): (Vec<_>, Vec<_>) = document
+        .select(&SECTION_PRE)
+        // Sections with a heading are the statement or labeled samples, which are parsed by labels
+        .filter(|pre| {
+            pre.parent()
+                .and_then(ElementRef::wrap)
+                .map_or(true, |section| section.select(&H3).next().is_none())
+        })
+        .map(sample_text)
+        .enumerate()
+        .partition(|(index, _)| index % 2 == 0);
+    debug!(
+        inputs = inputs.len(),
+        outputs = outputs.len(),
+        "matched samples in sections without headings"
+    );
+    (
+        inputs.into_iter().map(|(_, text)| text).collect(),
+        outputs.into_iter().map(|(_, text)| text).collect(),
+    )
+}
+
+/// Parse samples of the pre-2013 layout, in which each row of a table has a `<th>` header
+/// (e.g. "入力例 1") and the sample in the `<td>` next to it
+fn parse_table_samples(
+    document: &Html,
+    (input_label, output_label): (&str, &str),
+) -> (Vec<String>, Vec<String>) {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for tr in document.select(&TABLE_ROWS) {
+        let header = match tr.select(&TH).next() {
+            Some(th) => th.text().collect::<String>(),
+            None => continue,
+        };
+        let samples = if header.trim().starts_with(input_label) {
+            &mut inputs
+        } else if header.trim().starts_with(output_label) {
+            &mut outputs
+        } else {
+            continue;
+        };
+        // The sample may or may not be wrapped in `<pre>`
+        if let Some(td) = tr.select(&TD).next() {
+            samples.push(match td.select(&PRE).next() {
+                Some(pre) => sample_text(pre),
+                None => sample_text(td),
+            });
+        }
+    }
+    debug!(
+        label = input_label,
+        inputs = inputs.len(),
+        outputs = outputs.len(),
+        "matched samples in tables"
+    );
+    (inputs, outputs)
+}
+
+/// A sample case of a task
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    pub input: String,
+    pub output: String,
+}
+
+/// Parse all sample cases in the task page
+///
+/// HTML entities in samples are decoded.
+/// If no samples have labels, `section > pre` in sections without headings are taken
+/// as inputs and outputs alternately.
+/// `Error::Parse` is returned if no samples are found, i.e. the layout of the page is unknown.
+///
+/// # Examples
+/// ```
+/// let html = r#"<div id="task-statement">
+///   <div class="part"><h3>入力例 1</h3><pre>1 2</pre></div>
+///   <div class="part"><h3>出力例 1</h3><pre>3</pre></div>
+/// </div>"#;
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples[0].input, "1 2");
+/// assert_eq!(samples[0].output, "3");
+///
+/// let html = r#"<div id="task-statement">
+///   <div class="part"><h3>入力例 1</h3><pre>a &lt; b &amp;&amp; &quot;c&quot; &gt; &#x64;</pre></div>
+///   <div class="part"><h3>出力例 1</h3><pre>Yes</pre></div>
+/// </div>"#;
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples[0].input, r#"a < b && "c" > d"#);
+///
+/// // Only text nodes are taken, even if they are split by highlighting spans
+/// let html = r#"<div id="task-statement">
+///   <div class="part"><h3>入力例 1</h3><pre><span class="x">1</span>2 <span>3</span></pre></div>
+///   <div class="part"><h3>出力例 1</h3><pre><span>15</span></pre></div>
+/// </div>"#;
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples[0].input, "12 3");
+/// assert_eq!(samples[0].output, "15");
+///
+/// // CRLF in old pages is normalized to LF
+/// let html = "<div id=\"task-statement\">
+///   <div class=\"part\"><h3>入力例 1</h3><pre>1&#13;\n2&#13;\n</pre></div>
+///   <div class=\"part\"><h3>出力例 1</h3><pre>3&#13;\n</pre></div>
+/// </div>";
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples[0].input, "1\n2\n");
+/// assert_eq!(samples[0].output, "3\n");
+///
+/// // Tables of the oldest pages have the sample next to its header
+/// let html = r#"<table>
+///   <tr><th>入力例 1</th><td><pre>1 2</pre></td></tr>
+///   <tr><th>出力例 1</th><td>3</td></tr>
+/// </table>"#;
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples[0].input, "1 2");
+/// assert_eq!(samples[0].output, "3");
+///
+/// // Every pair is taken in order
+/// let html = r#"<div id="task-statement">
+///   <div class="part"><h3>入力例 1</h3><pre>1</pre></div>
+///   <div class="part"><h3>出力例 1</h3><pre>one</pre></div>
+///   <div class="part"><h3>入力例 2</h3><pre>2</pre></div>
+///   <div class="part"><h3>出力例 2</h3><pre>two</pre></div>
+///   <div class="part"><h3>入力例 3</h3><pre>3</pre></div>
+///   <div class="part"><h3>出力例 3</h3><pre>three</pre></div>
+/// </div>"#;
+/// let samples = atcoder4rust::parse_samples(html).unwrap();
+/// assert_eq!(samples.len(), 3);
+/// assert_eq!(samples[2].input, "3");
+/// assert_eq!(samples[2].output, "three");
+///
+/// // Pages without samples, or not even HTML, are errors rather than panics
+/// let html = r#"<div id="task-statement"><div class="part"><h3>問題文</h3></div></div>"#;
+/// assert!(atcoder4rust::parse_samples(html).is_err());
+/// assert!(atcoder4rust::parse_samples("<<<pre>\0</h3>").is_err());
+/// ```
+pub fn parse_samples(text: &str) -> Result<Vec<Sample>, Error> {
+    let document = Html::parse_document(&text);
+    // Prefer the labels of the document's language, and fall back to the other one
+    let is_english = document
+        .root_element()
+        .value()
+        .attr("lang")
+        .map_or(false, |lang| lang.starts_with("en"));
+    let labels = if is_english {
+        [ENGLISH_LABELS, JAPANESE_LABELS]
+    } else {
+        [JAPANESE_LABELS, ENGLISH_LABELS]
+    };
+    let candidates: Vec<_> = PARTS
+        .iter()
+        .flat_map(|part| labels.iter().map(move |labels| (part, *labels)))
+        .map(|(part, labels)| parse_samples_with(&document, part, labels))
+        // The table of the oldest pages is tried next, and `section > pre` without labels last
+        .chain(
+            labels
+                .iter()
+                .map(|labels| parse_table_samples(&document, *labels)),
+        )
+        .chain(iter::once(parse_bare_section_samples(&document)))
+        .filter(|(inputs, outputs)| !inputs.is_empty() || !outputs.is_empty())
+        .collect();
+    if candidates.is_empty() {
+        return Err(Error::Parse("No samples found".to_owned()));
+    }
+    // Prefer the layout in which every input has its output
+    let (inputs, outputs) = candidates
+        .iter()
+        .find(|(inputs, outputs)| inputs.len() == outputs.len())
+        .or_else(|| candidates.first())
+        .cloned()
+        .unwrap_or_default();
+    Ok(inputs
+        .into_iter()
+        .zip(outputs)
+        .map(|(input, output)| Sample { input, output })
+        .collect())
+}
+
+/// Series of a contest, whose task pages may have a layout of their own
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContestType {
+    /// AtCoder Beginner Contest
+    Abc,
+    /// AtCoder Regular Contest
+    Arc,
+    /// AtCoder Grand Contest
+    Agc,
+    /// AtCoder Heuristic Contest
+    Ahc,
+    /// Any other contest, e.g. sponsored ones
+    Other,
+}
+
+impl ContestType {
+    /// Get the series from the prefix of the contest ID
+    ///
+    /// # Examples
+    /// ```
+    /// use atcoder4rust::ContestType;
+    ///
+    /// assert_eq!(ContestType::from_id("abc001"), ContestType::Abc);
+    /// assert_eq!(ContestType::from_id("ARC058"), ContestType::Arc);
+    /// assert_eq!(ContestType::from_id("practice"), ContestType::Other);
+    /// ```
+    pub fn from_id(id: &str) -> Self {
+        let id = id.to_lowercase();
+        if id.starts_with("abc") {
+            Self::Abc
+        } else if id.starts_with("arc") {
+            Self::Arc
+        } else if id.starts_with("agc") {
+            Self::Agc
+        } else if id.starts_with("ahc") {
+            Self::Ahc
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Get the series from the URL of a page in the contest, e.g. `/contests/abc001/tasks/abc001_1`
+    fn from_url(url: &Url) -> Self {
+        url.path_segments()
+            .and_then(|mut segments| {
+                segments.find(|segment| *segment == "contests")?;
+                segments.next()
+            })
+            .map_or(Self::Other, Self::from_id)
+    }
+}
+
+/// Parse samples of the legacy ARC/AGC layout (2015–2018),
+/// in which a table in `div.io-style` has the input and output of each sample side by side
+fn parse_legacy_samples(document: &Html) -> Vec<Sample> {
+    document
+        .select(&LEGACY_SAMPLE_ROWS)
+        .filter_map(|tr| {
+            let cells: Vec<_> = tr
+                .select(&TD)
+                .filter_map(|td| td.select(&PRE).next())
+                .map(sample_text)
+                .collect();
+            match cells.as_slice() {
+                [input, output] => Some(Sample {
+                    input: input.clone(),
+                    output: output.clone(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parse all sample cases in the task page with the parser for the contest's series
+///
+/// ARC and AGC pages are tried with the legacy table layout first,
+/// and every series falls back to `parse_samples`.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::ContestType;
+///
+/// let html = r#"<div id="task-statement"><div class="io-style"><table>
+///   <tr><td><pre>1 2</pre></td><td><pre>3</pre></td></tr>
+/// </table></div></div>"#;
+/// let samples = atcoder4rust::parse_contest_samples(ContestType::Arc, html).unwrap();
+/// assert_eq!(samples[0].input, "1 2");
+/// assert_eq!(samples[0].output, "3");
+/// ```
+pub fn parse_contest_samples(contest_type: ContestType, text: &str) -> Result<Vec<Sample>, Error> {
+    match contest_type {
+        ContestType::Arc | ContestType::Agc => {
+            let samples = parse_legacy_samples(&Html::parse_document(text));
+            if samples.is_empty() {
+                parse_samples(text)
+            } else {
+                Ok(samples)
+            }
+        }
+        ContestType::Abc | ContestType::Ahc | ContestType::Other => parse_samples(text),
+    }
+}
+
+/// Time and memory limits and the score of a task
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemMeta {
+    /// e.g. "2 sec"
+    pub time_limit: Option<String>,
+    /// e.g. "1024 MB"
+    pub memory_limit: Option<String>,
+    /// Whether the statement says that the task is interactive
+    pub interactive: bool,
+    /// Points of the task in the table of tasks or the statement,
+    /// which is missing in samples cached by older versions
+    #[serde(default)]
+    pub score: Option<u32>,
+}
+
+/// Everything parsed from a task page
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    /// e.g. "A - Two Integers"
+    pub title: Option<String>,
+    /// Sample cases
+    pub samples: Vec<Sample>,
+    /// Time and memory limits
+    pub meta: ProblemMeta,
+    /// HTML of `#task-statement` whose images have absolute URLs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement_html: Option<String>,
+}
+
+fn parse_limit(document: &Html, selector: &Selector) -> Option<String> {
+    let text = document.select(selector).next()?.text().collect::<String>();
+    // Drop the label such as "Time Limit:"
+    let limit = text.rsplit(':').next().unwrap_or_default().trim();
+    if limit.is_empty() {
+        None
+    } else {
+        Some(limit.to_owned())
+    }
+}
+
+/// Parse the time limit, the memory limit, the score and whether the task is interactive
+/// in the task page
+///
+/// Limits and the score which could not be found are left as `None`.
+///
+/// # Examples
+/// ```
+/// let html = r#"<span class="time-limit">Time Limit: 2 sec</span>
+/// <span class="memory-limit">Memory Limit: 1024 MB</span>
+/// <div id="task-statement"><p>配点 : <var>300</var> 点</p></div>"#;
+/// let meta = atcoder4rust::parse_problem_meta(html).unwrap();
+/// assert_eq!(meta.time_limit, Some("2 sec".to_string()));
+/// assert_eq!(meta.memory_limit, Some("1024 MB".to_string()));
+/// assert!(!meta.interactive);
+/// assert_eq!(meta.score, Some(300));
+/// ```
+pub fn parse_problem_meta(text: &str) -> Result<ProblemMeta, Error> {
+    let document = Html::parse_document(text);
+    Ok(ProblemMeta {
+        time_limit: parse_limit(&document, &TIME_LIMIT),
+        memory_limit: parse_limit(&document, &MEMORY_LIMIT),
+        interactive: document
+            .select(&TASK_STATEMENT)
+            .flat_map(|statement| statement.text())
+            .any(|text| text.contains("インタラクティブ") || text.contains("Interactive")),
+        score: document
+            .select(&TASK_STATEMENT)
+            .next()
+            .and_then(|statement| {
+                SCORE
+                    .captures(&statement.text().collect::<String>())
+                    .and_then(|captures| captures[1].parse().ok())
+            }),
+    })
+}
+
+/// Parse the title of the task, which is the first `<h2>` in the statement
+///
+/// # Examples
+/// ```
+/// let html = r#"<div id="task-statement"><h2>A - Two Integers</h2></div>"#;
+/// assert_eq!(
+///     atcoder4rust::parse_problem_title(html),
+///     Some("A - Two Integers".to_string())
+/// );
+/// ```
+pub fn parse_problem_title(text: &str) -> Option<String> {
+    let document = Html::parse_document(text);
+    let title = document
+        .select(&TASK_TITLE)
+        .next()?
+        .text()
+        .collect::<String>();
+    // Titles may be broken into lines in the page
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Append the element as Markdown, keeping headings, paragraphs, lists and preformatted blocks
+fn push_markdown(markdown: &mut String, element: ElementRef) {
+    let text = || {
+        element
+            .text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match element.value().name() {
+        "h2" | "h3" | "h4" => {
+            markdown.push_str(&format!("## {}\n\n", text()));
+        }
+        "p" => markdown.push_str(&format!("{}\n\n", text())),
+        "pre" => markdown.push_str(&format!(
+            "```\n{}\n```\n\n",
+            element.text().collect::<String>().trim_end()
+        )),
+        "li" => markdown.push_str(&format!("- {}\n", text())),
+        "ul" | "ol" => {
+            element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .for_each(|child| push_markdown(markdown, child));
+            markdown.push('\n');
+        }
+        _ => element.children().for_each(|child| match child.value() {
+            Node::Text(text) if !text.trim().is_empty() => {
+                markdown.push_str(&format!("{}\n\n", text.trim()))
+            }
+            _ => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    push_markdown(markdown, child)
+                }
+            }
+        }),
+    }
+}
+
+/// Extract the HTML of the statement of the task, rewriting `src` of images to absolute URLs
+/// so that it renders when it is opened from disk
+///
+/// `None` is returned if the page has no statement.
+///
+/// # Examples
+/// ```
+/// use reqwest::Url;
+///
+/// let html = r#"<div id="task-statement"><p>Figure</p><img src="/img/other/abc001_1.png"></div>"#;
+/// let url = Url::parse("https://atcoder.jp/contests/abc001/tasks/abc001_1").unwrap();
+/// let statement = atcoder4rust::parse_statement_html(html, &url).unwrap();
+/// assert!(statement.contains(r#"src="https://atcoder.jp/img/other/abc001_1.png""#));
+/// assert_eq!(atcoder4rust::parse_statement_html("<p></p>", &url), None);
+/// ```
+pub fn parse_statement_html(text: &str, url: &Url) -> Option<String> {
+    let document = Html::parse_document(text);
+    let statement = document.select(&TASK_STATEMENT).next()?.html();
+    Some(
+        IMG_SRC
+            .replace_all(&statement, |captures: &regex::Captures| {
+                let src = url
+                    .join(&captures[2])
+                    .map_or_else(|_| captures[2].to_owned(), String::from);
+                format!("{}{}\"", &captures[1], src)
+            })
+            .into_owned(),
+    )
+}
+
+/// Parse the statement of the task, including constraints and samples, as Markdown
+///
+/// Only the part in the language of the page is taken if the statement has both languages.
+///
+/// # Examples
+/// ```
+/// let html = r#"<div id="task-statement"><span class="lang"><span class="lang-ja">
+///   <div class="part"><section><h3>問題文</h3><p>整数 <var>A</var> が与えられます。</p></section></div>
+/// </span></span></div>"#;
+/// let statement = atcoder4rust::parse_problem_statement(html);
+/// assert_eq!(statement, "## 問題文\n\n整数 A が与えられます。\n\n");
+/// ```
+pub fn parse_problem_statement(text: &str) -> String {
+    let document = Html::parse_document(text);
+    let is_english = document
+        .root_element()
+        .value()
+        .attr("lang")
+        .map_or(false, |lang| lang.starts_with("en"));
+    let language = if is_english {
+        "#task-statement .lang-en"
+    } else {
+        "#task-statement .lang-ja"
+    };
+    let mut markdown = String::new();
+    if let Some(statement) = [language, "#task-statement"]
+        .iter()
+        .filter_map(|selectors| selector(selectors).ok())
+        .find_map(|statement| document.select(&statement).next())
+    {
+        push_markdown(&mut markdown, statement);
+    }
+    markdown
+}
+
+#[instrument(skip(client, url, cookies, retry), fields(url = %url))]
+async fn fetch_task(
+    client: &Client,
+    url: &Url,
+    cookies: &HeaderMap,
+    retry: &RetryPolicy,
+) -> Result<Task, Error> {
+    let response =
+        send_with_retry(|| client.get(url.clone()).headers(cookies.clone()), retry).await?;
+    // Pages of errors have no samples, and must not be generated as tasks without samples
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    let redirected_to_login = response.url().path().ends_with("/login");
+    let text = response.text().await?;
+    if redirected_to_login || is_login_page(&text) {
+        return Err(Error::Auth(format!("{} requires login", url)));
+    }
+    // A task without samples should not stop the others from being generated
+    let samples = match parse_contest_samples(ContestType::from_url(url), &text) {
+        Err(Error::Parse(message)) => {
+            warn!("{}: {}", url, message);
+            Vec::new()
+        }
+        samples => samples?,
+    };
+    Ok(Task {
+        title: parse_problem_title(&text),
+        samples,
+        meta: parse_problem_meta(&text)?,
+        statement_html: parse_statement_html(&text, url),
+    })
+}
+
+/// A task listed in the tasks page of a contest
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TaskLink {
+    /// e.g. "A"
+    pub name: String,
+    /// URL of the task page, which may be relative to the root of AtCoder
+    pub url: String,
+    /// Points in the cell following the task name, if the table has the column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+}
+
+/// Points in a cell of the table of tasks, e.g. "300", "300 points" or "300 点"
+fn parse_score(cell: &str) -> Option<u32> {
+    let cell = cell.trim();
+    cell.strip_suffix("points")
+        .or_else(|| cell.strip_suffix('点'))
+        .unwrap_or(cell)
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Parse the table of tasks in the tasks page of a contest
+///
+/// The score is taken from the cell following the task name, which is the third one of the row.
+/// AtCoder's own tables have no such column (the cell is the time limit),
+/// so their scores are `None` and `get_samples` takes them from the statements instead.
+///
+/// # Examples
+/// ```
+/// let html = r#"<table><tbody>
+///   <tr><td><a href="/contests/abc001/tasks/abc001_1">A</a></td></tr>
+///   <tr><td><a href="/contests/abc001/tasks/abc001_2">B</a></td><td>Name</td><td>200</td></tr>
+/// </tbody></table>"#;
+/// let tasks = atcoder4rust::parse_task_list(html).unwrap();
+/// assert_eq!(tasks[0].name, "A");
+/// assert_eq!(tasks[0].url, "/contests/abc001/tasks/abc001_1");
+/// assert_eq!(tasks[0].score, None);
+/// assert_eq!(tasks[1].score, Some(200));
+/// ```
+pub fn parse_task_list(text: &str) -> Result<Vec<TaskLink>, Error> {
+    let document = Html::parse_document(text);
+    let link_selector = selector("td a")?;
+    Ok(document
+        .select(&selector("tbody > tr")?)
+        .filter_map(|tr| {
+            let a = tr.select(&link_selector).next()?;
+            let score = tr
+                .select(&TD)
+                .nth(2)
+                .and_then(|td| parse_score(&td.text().collect::<String>()));
+            a.value().attr("href").map(|url| TaskLink {
+                name: a.inner_html(),
+                url: url.to_owned(),
+                score,
+            })
+        })
+        .collect())
+}
+
+/// Fetch all task pages listed in the tasks page and parse their sample cases and limits
+///
+/// The returned map is keyed by task names (e.g. "A"), in the order of the table of tasks.
+/// Samples are parsed with the parser for the series of the contest in the task's URL.
+/// At most `parallel` task pages are fetched at the same time.
+/// If `progress` is given, a spinner is shown for each task page while it is fetched.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() -> Result<(), atcoder4rust::Error> {
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::new();
+/// let root_url = Url::parse("https://atcoder.jp/")?;
+/// let html = client
+///     .get(root_url.join("contests/abc001/tasks")?)
+///     .send()
+///     .await?
+///     .text()
+///     .await?;
+/// let samples =
+///     atcoder4rust::get_samples(&html, &client, &root_url, &None, &Default::default(), 4, None)
+///         .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_samples(
+    text: &str,
+    client: &Client,
+    root_url: &Url,
+    cookies: &Option<HeaderMap>,
+    retry: &RetryPolicy,
+    parallel: usize,
+    progress: Option<Arc<MultiProgress>>,
+) -> Result<IndexMap<String, Task>, Error> {
+    let task_links = parse_task_list(text)?;
+    let samples: Vec<_> = task_links
+        .iter()
+        .map(|TaskLink { name, url, score }| {
+            let task_name = name.clone();
+            let score = *score;
+            let url = url.as_str();
+            let root_url = root_url.clone();
+            let client = client.clone();
+            let cookies = cookies.clone().unwrap_or_default();
+            let retry = *retry;
+            // Bars must be added before the progress starts to be drawn
+            let bar = progress.clone().map(|progress| {
+                let bar = progress.add(ProgressBar::new_spinner());
+                bar.set_style(ProgressStyle::default_spinner().template("{spinner} {msg}"));
+                bar.set_message(&format!("{} ({})", task_name, url));
+                bar.enable_steady_tick(100);
+                bar
+            });
+            async move {
+                let result = match root_url.join(url) {
+                    Ok(url) => fetch_task(&client, &url, &cookies, &retry).await,
+                    Err(error) => Err(error.into()),
+                };
+                if let Some(bar) = bar {
+                    let mark = if result.is_ok() { "✓" } else { "✗" };
+                    bar.finish_with_message(&format!("{} {} ({})", mark, task_name, url));
+                }
+                result.map(|mut task| {
+                    // The table of tasks takes precedence over the statement
+                    task.meta.score = score.or(task.meta.score);
+                    (task_name, task)
+                })
+            }
+        })
+        .collect();
+    let drawer = progress.map(|progress| thread::spawn(move || progress.join()));
+    let samples: Vec<_> = stream::iter(samples)
+        // Unlike buffer_unordered, the results keep the order of the table
+        .buffered(parallel.max(1))
+        .collect()
+        .await;
+    if let Some(drawer) = drawer {
+        drawer
+            .join()
+            .map_err(|_| Error::Invalid("Failed to draw the progress".to_owned()))??;
+    }
+    samples.into_iter().collect()
+}
+
+/// Login to AtCoder and return the cookies of the session
+///
+/// Whether it succeeded is told by the page which the login form redirects to, rather than
+/// by the cookies: `Error::Auth` is returned if it is the login page again.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() -> Result<(), atcoder4rust::Error> {
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::builder().cookie_store(true).build()?;
+/// let url = Url::parse("https://atcoder.jp/login")?;
+/// let cookies =
+///     atcoder4rust::login(url, &client, "username", "password", &Default::default()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(skip(url, client, password, retry))]
+pub async fn login(
+    url: Url,
+    client: &Client,
+    username: &str,
+    password: &str,
+    retry: &RetryPolicy,
+) -> Result<HeaderMap, Error> {
+    let response = send_with_retry(|| client.get(url.clone()), retry).await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    let csrf_token = get_csrf_token(&set_cookie_headers(&response))?;
+    let login_path = url.path().to_owned();
+    let response = client
+        .post(url)
+        .headers(get_cookies(&response))
+        .form(&[
+            ("username", username),
+            ("password", password),
+            ("csrf_token", &csrf_token),
+        ])
+        .send()
+        .await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    let cookies = get_cookies(&response);
+    // AtCoder redirects to the login page again with an alert if it fails
+    if response.url().path() == login_path {
+        let document = Html::parse_document(&response.text().await?);
+        let alert = document
+            .select(&selector(".alert-danger")?)
+            .next()
+            .map(|alert| alert.text().collect::<String>())
+            .unwrap_or_default();
+        let alert = alert.split_whitespace().collect::<Vec<_>>().join(" ");
+        return Err(Error::Auth(
+            if alert.contains("locked") || alert.contains("ロック") {
+                format!("account locked ({})", alert)
+            } else if alert.is_empty() {
+                "wrong credentials".to_owned()
+            } else {
+                format!("wrong credentials ({})", alert)
+            },
+        ));
+    }
+    Ok(cookies)
+}
+
+/// Check whether the page is shown to a logged-in user, who has a link to the settings
+///
+/// # Examples
+/// ```
+/// assert!(atcoder4rust::is_logged_in(r#"<a href="/settings">Settings</a>"#));
+/// assert!(!atcoder4rust::is_logged_in(r#"<a href="/login">Sign In</a>"#));
+/// ```
+pub fn is_logged_in(text: &str) -> bool {
+    let document = Html::parse_document(text);
+    selector(r#"a[href$="/settings"]"#).map_or(false, |settings| {
+        document.select(&settings).next().is_some()
+    })
+}
+
+/// Check whether the page is the login form, which AtCoder shows instead of private pages
+///
+/// # Examples
+/// ```
+/// let html = r#"<form action="/login" method="POST"><input type="password" name="password"></form>"#;
+/// assert!(atcoder4rust::is_login_page(html));
+/// assert!(!atcoder4rust::is_login_page("<table><tbody></tbody></table>"));
+/// ```
+pub fn is_login_page(text: &str) -> bool {
+    let document = Html::parse_document(text);
+    selector(r#"form input[name="password"]"#).map_or(false, |password| {
+        document.select(&password).next().is_some()
+    })
+}
+
+/// Check whether the session of the cookies is still active by fetching `/home`
+///
+/// # Examples
+/// ```no_run
+/// # async fn run(cookies: reqwest::header::HeaderMap) -> Result<(), atcoder4rust::Error> {
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::new();
+/// let root_url = Url::parse("https://atcoder.jp/")?;
+/// if !atcoder4rust::validate_session(&client, &root_url, &cookies, &Default::default()).await? {
+///     // login again
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn validate_session(
+    client: &Client,
+    root_url: &Url,
+    cookies: &HeaderMap,
+    retry: &RetryPolicy,
+) -> Result<bool, Error> {
+    let url = root_url.join("home")?;
+    let response =
+        send_with_retry(|| client.get(url.clone()).headers(cookies.clone()), retry).await?;
+    if response.status() != StatusCode::OK {
+        return Ok(false);
+    }
+    Ok(is_logged_in(&response.text().await?))
+}
+
+/// Language ID of Rust in the submit form of AtCoder
+pub const RUST_LANGUAGE_ID: &str = "4050";
+
+/// A language in the submit form of a contest
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Language {
+    /// e.g. "4050"
+    pub id: String,
+    /// e.g. "Rust (rustc 1.42.0)"
+    pub name: String,
+}
+
+/// Parse the languages in the submit page, without duplicates among the forms of the tasks
+///
+/// # Examples
+/// ```
+/// let html = r#"<select name="data.LanguageId">
+///   <option value="4003">C++ (GCC 9.2.1)</option>
+///   <option value="4050">Rust (1.42.0)</option>
+/// </select>"#;
+/// let languages = atcoder4rust::parse_languages(html).unwrap();
+/// assert_eq!(languages[1].id, "4050");
+/// assert_eq!(languages[1].name, "Rust (1.42.0)");
+/// ```
+pub fn parse_languages(text: &str) -> Result<Vec<Language>, Error> {
+    let document = Html::parse_document(text);
+    let mut languages: Vec<Language> = Vec::new();
+    for option in document.select(&selector(r#"select[name="data.LanguageId"] option"#)?) {
+        let id = match option.value().attr("value") {
+            Some(id) if !id.is_empty() => id,
+            _ => continue,
+        };
+        if languages.iter().all(|language| language.id != id) {
+            languages.push(Language {
+                id: id.to_owned(),
+                name: option.text().collect::<String>().trim().to_owned(),
+            });
+        }
+    }
+    Ok(languages)
+}
+
+/// Fetch the submit page of the contest and parse its languages
+///
+/// # Examples
+/// ```no_run
+/// # async fn run(cookies: reqwest::header::HeaderMap) -> Result<(), atcoder4rust::Error> {
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::builder().cookie_store(true).build()?;
+/// let root_url = Url::parse("https://atcoder.jp/")?;
+/// let languages =
+///     atcoder4rust::get_languages(&client, &root_url, "abc001", &cookies, &Default::default())
+///         .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_languages(
+    client: &Client,
+    root_url: &Url,
+    contest_id: &str,
+    cookies: &HeaderMap,
+    retry: &RetryPolicy,
+) -> Result<Vec<Language>, Error> {
+    let url = root_url.join(&format!("contests/{}/submit", contest_id))?;
+    let response =
+        send_with_retry(|| client.get(url.clone()).headers(cookies.clone()), retry).await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    parse_languages(&response.text().await?)
+}
+
+/// Parse the csrf token and the screen name of the task (e.g. "abc001_a") in the submit page
+fn parse_submit_page(text: &str, contest_id: &str, task: &str) -> Result<(String, String), Error> {
+    let document = Html::parse_document(text);
+    let csrf_token = document
+        .select(&selector(r#"input[name="csrf_token"]"#)?)
+        .filter_map(|input| input.value().attr("value"))
+        .next()
+        .ok_or_else(|| Error::Invalid("Could not find csrf_token".to_owned()))?
+        .to_owned();
+    let label = format!("{} -", task.to_uppercase());
+    let task_screen_name = document
+        .select(&selector(r#"select[name="data.TaskScreenName"] option"#)?)
+        .find(|option| option.text().collect::<String>().trim().starts_with(&label))
+        .and_then(|option| option.value().attr("value"))
+        .map(|value| value.to_owned())
+        .unwrap_or_else(|| format!("{}_{}", contest_id, task.to_lowercase()));
+    Ok((csrf_token, task_screen_name))
+}
+
+/// Submit the source code to the task as the language of `language_id` (e.g. `RUST_LANGUAGE_ID`),
+/// and return the URL of the page which the response was redirected to (i.e. the list of own submissions)
+///
+/// # Examples
+/// ```no_run
+/// # async fn run(cookies: reqwest::header::HeaderMap) -> Result<(), atcoder4rust::Error> {
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::builder().cookie_store(true).build()?;
+/// let root_url = Url::parse("https://atcoder.jp/")?;
+/// let source = "fn main() {}";
+/// let url = atcoder4rust::submit(
+///     &client,
+///     &root_url,
+///     "abc001",
+///     "a",
+///     source,
+///     atcoder4rust::RUST_LANGUAGE_ID,
+///     &cookies,
+///     &Default::default(),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn submit(
+    client: &Client,
+    root_url: &Url,
+    contest_id: &str,
+    task: &str,
+    source: &str,
+    language_id: &str,
+    cookies: &HeaderMap,
+    retry: &RetryPolicy,
+) -> Result<Url, Error> {
+    let url = root_url.join(&format!("contests/{}/submit", contest_id))?;
+    let response =
+        send_with_retry(|| client.get(url.clone()).headers(cookies.clone()), retry).await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    let (csrf_token, task_screen_name) =
+        parse_submit_page(&response.text().await?, contest_id, task)?;
+    // Never retry this request, or the source may be submitted twice
+    let response = client
+        .post(url)
+        .headers(cookies.clone())
+        .form(&[
+            ("data.TaskScreenName", task_screen_name.as_str()),
+            ("data.LanguageId", language_id),
+            ("sourceCode", source),
+            ("csrf_token", csrf_token.as_str()),
+        ])
+        .send()
+        .await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    Ok(response.url().clone())
+}
+
+/// Parse the status (e.g. "AC", "WJ", "3/12") of the latest submission in the list of submissions
+///
+/// # Examples
+/// ```
+/// let html = r#"<table><tbody>
+///   <tr><td>A - Test</td><td class="text-center"><span class="label">AC</span></td></tr>
+/// </tbody></table>"#;
+/// assert_eq!(atcoder4rust::parse_latest_status(html), Some("AC".to_string()));
+/// ```
+pub fn parse_latest_status(text: &str) -> Option<String> {
+    let document = Html::parse_document(text);
+    let row = document.select(&selector("tbody > tr").ok()?).next()?;
+    let status = row.select(&selector("td > span.label").ok()?).next()?;
+    Some(status.text().collect::<String>().trim().to_owned())
+}
+
+fn is_judging(status: &str) -> bool {
+    status.is_empty() || status == "WJ" || status == "WR" || status.contains('/')
+}
+
+/// Poll the list of submissions until the latest submission is judged, and return its verdict
+///
+/// `Error::Invalid` is returned if it is still being judged after `timeout`.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run(cookies: reqwest::header::HeaderMap) -> Result<(), atcoder4rust::Error> {
+/// use std::time::Duration;
+///
+/// use reqwest::{Client, Url};
+///
+/// let client = Client::builder().cookie_store(true).build()?;
+/// let url = Url::parse("https://atcoder.jp/contests/abc001/submissions/me")?;
+/// let verdict = atcoder4rust::wait_for_verdict(
+///     &client,
+///     &url,
+///     &cookies,
+///     &Default::default(),
+///     Duration::from_secs(600),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn wait_for_verdict(
+    client: &Client,
+    url: &Url,
+    cookies: &HeaderMap,
+    retry: &RetryPolicy,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let start = Instant::now();
+    loop {
+        let response =
+            send_with_retry(|| client.get(url.clone()).headers(cookies.clone()), retry).await?;
+        if response.status() != StatusCode::OK {
+            return Err(Error::Http(response.status()));
+        }
+        let status = parse_latest_status(&response.text().await?)
+            .ok_or_else(|| Error::Invalid("Could not find the submission".to_owned()))?;
+        if !is_judging(&status) {
+            return Ok(status);
+        }
+        if start.elapsed() >= timeout {
+            return Err(Error::Invalid(format!(
+                "The submission is still {} after {:?}",
+                status, timeout
+            )));
+        }
+        info!(%status, "judging");
+        tokio::time::delay_for(Duration::from_secs(2)).await;
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use atcoder4rust::{Error, Task};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Tasks of a contest cached on the disk, keyed by task names as `get_samples` returns them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SampleCache {
+    /// When the tasks were fetched, which is written in ISO 8601
+    pub fetched_at: DateTime<Utc>,
+    pub tasks: IndexMap<String, Task>,
+}
+
+impl SampleCache {
+    /// Path to the cache file of the contest
+    pub fn path(cache_dir: &Path, contest_id: &str) -> PathBuf {
+        cache_dir.join(contest_id).join("samples.json")
+    }
+
+    /// Load the cached tasks of the contest, unless they are missing or older than `ttl`
+    pub fn load(cache_dir: &Path, contest_id: &str, ttl: Duration) -> Result<Option<Self>, Error> {
+        let path = Self::path(cache_dir, contest_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let cache: Self = serde_json::from_reader(BufReader::new(File::open(&path)?))?;
+        // A clock set backwards makes the age negative, which is regarded as expired as well
+        let fresh = Utc::now()
+            .signed_duration_since(cache.fetched_at)
+            .to_std()
+            .map_or(false, |age| age < ttl);
+        debug!(path = %path.display(), fetched_at = %cache.fetched_at, fresh, "sample cache");
+        Ok(if fresh { Some(cache) } else { None })
+    }
+
+    /// Save the tasks of the contest with the current time
+    pub fn save(
+        cache_dir: &Path,
+        contest_id: &str,
+        tasks: &IndexMap<String, Task>,
+    ) -> Result<(), Error> {
+        let path = Self::path(cache_dir, contest_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let writer = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(
+            writer,
+            &Self {
+                fetched_at: Utc::now(),
+                tasks: tasks.clone(),
+            },
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Read, Write},
+    path::PathBuf,
+};
+
+use atcoder4rust::Error;
+use serde::Deserialize;
+use tracing::warn;
+
+/// Keys which `Config` knows
+const KNOWN_KEYS: [&str; 17] = [
+    "user",
+    "cookie",
+    "no_login",
+    "root",
+    "dependencies",
+    "template",
+    "test_framework",
+    "retries",
+    "retry_delay",
+    "max_retry_wait",
+    "timeout",
+    "connect_timeout",
+    "parallel",
+    "compare_mode",
+    "float_tolerance",
+    "edition",
+    "layout",
+];
+
+/// Template written by `config init`
+const TEMPLATE: &str = r#"# Configuration of atcoder4rust
+# Each value is used as the default of the command line option of the same name,
+# and the command line option overrides it.
+
+# user = "your name"
+# cookie = "/path/to/cookie.txt"
+# no_login = false
+# root = "/path/to/projects"
+# dependencies = "/path/to/dependencies.toml"
+# template = "/path/to/template.rs"
+# test_framework = "assert_cmd"
+# retries = 3
+# retry_delay = 1
+# max_retry_wait = 60
+# timeout = 30
+# connect_timeout = 10
+# parallel = 4
+# compare_mode = "exact"
+# float_tolerance = 1e-6
+# edition = "2021"
+# layout = "modules"
+"#;
+
+/// Default values of the command line options
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub user: Option<String>,
+    pub cookie: Option<String>,
+    pub no_login: Option<bool>,
+    pub root: Option<String>,
+    pub dependencies: Option<String>,
+    pub template: Option<String>,
+    pub test_framework: Option<String>,
+    pub retries: Option<usize>,
+    pub retry_delay: Option<u64>,
+    pub max_retry_wait: Option<u64>,
+    pub timeout: Option<u64>,
+    pub connect_timeout: Option<u64>,
+    pub parallel: Option<usize>,
+    pub compare_mode: Option<String>,
+    pub float_tolerance: Option<f64>,
+    pub edition: Option<String>,
+    pub layout: Option<String>,
+}
+
+impl Config {
+    /// Path to the config file (`$XDG_CONFIG_HOME/atcoder4rust/config.toml`)
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join("atcoder4rust").join("config.toml"))
+    }
+
+    /// Load the config file, or use the default if it does not exist
+    pub fn load() -> Result<Self, Error> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Self::default()),
+        };
+        let mut reader = BufReader::new(File::open(&path)?);
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+        let value: toml::Value = toml::from_str(&buf)?;
+        if let Some(table) = value.as_table() {
+            // Unknown keys may be the ones of future versions
+            table
+                .keys()
+                .filter(|key| !KNOWN_KEYS.contains(&key.as_str()))
+                .for_each(|key| warn!("unknown key `{}` in {}", key, path.display()));
+        }
+        Ok(value.try_into()?)
+    }
+
+    /// Write the commented template to the config path
+    pub fn init() -> Result<PathBuf, Error> {
+        let path = Self::path()
+            .ok_or_else(|| Error::Invalid("Could not find the config directory".to_owned()))?;
+        if path.exists() {
+            return Err(Error::Invalid(format!("{} already exists", path.display())));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&path)?
+            .write_all(TEMPLATE.as_bytes())?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The template without the comment signs of the values
+    fn uncommented_template() -> String {
+        TEMPLATE
+            .lines()
+            .filter_map(|line| line.strip_prefix("# "))
+            .filter(|line| line.contains(" = "))
+            .map(|line| line.to_owned() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn template_has_every_known_key() {
+        let template: toml::Value = toml::from_str(&uncommented_template()).unwrap();
+        let table = template.as_table().unwrap();
+        assert_eq!(table.len(), KNOWN_KEYS.len());
+        for key in &KNOWN_KEYS {
+            assert!(table.contains_key(*key), "{} is not in the template", key);
+        }
+    }
+
+    #[test]
+    fn template_values_are_valid() {
+        let config: Config = toml::from_str(&uncommented_template()).unwrap();
+        assert_eq!(config.timeout, Some(30));
+        assert_eq!(config.parallel, Some(4));
+        assert_eq!(config.compare_mode.as_deref(), Some("exact"));
+        assert_eq!(config.float_tolerance, Some(1e-6));
+        assert_eq!(config.layout.as_deref(), Some("modules"));
+    }
+}
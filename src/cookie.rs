@@ -0,0 +1,318 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use atcoder4rust::Error;
+use fs2::FileExt;
+use keyring::{Keyring, KeyringError};
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tracing::debug;
+
+use crate::writer::crosses_devices;
+
+/// Formats of the cookie file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CookieFormat {
+    /// `name=value` per line
+    Text,
+    /// JSON array of `StoredCookie`
+    Json,
+}
+
+impl CookieFormat {
+    /// Names of the formats accepted by `from_name`
+    pub const NAMES: [&'static str; 2] = ["text", "json"];
+
+    /// Get the format from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the contents of a cookie file
+    pub fn detect(contents: &str) -> Self {
+        match contents.trim_start().chars().next() {
+            Some('[') | Some('{') => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// A cookie in the JSON cookie file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub expires: Option<String>,
+}
+
+/// Contents of the JSON cookie file, which may be a single cookie
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCookies {
+    Many(Vec<StoredCookie>),
+    One(StoredCookie),
+}
+
+/// Parse the contents of a cookie file into `Cookie` headers
+pub fn parse(contents: &str) -> Result<HeaderMap, serde_json::Error> {
+    let values: Vec<_> = match CookieFormat::detect(contents) {
+        CookieFormat::Text => contents.lines().map(str::to_owned).collect(),
+        CookieFormat::Json => match serde_json::from_str(contents)? {
+            StoredCookies::Many(cookies) => cookies,
+            StoredCookies::One(cookie) => vec![cookie],
+        }
+        .into_iter()
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect(),
+    };
+    let mut cookies = HeaderMap::new();
+    // `append` keeps every cookie under the same `Cookie` header name
+    for value in values
+        .iter()
+        .filter_map(|value| HeaderValue::from_str(value).ok())
+    {
+        cookies.append(header::COOKIE, value);
+    }
+    Ok(cookies)
+}
+
+/// Serialize `Cookie` headers in the format
+pub fn serialize(cookies: &HeaderMap, format: CookieFormat) -> Result<String, serde_json::Error> {
+    let values = cookies
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok());
+    Ok(match format {
+        CookieFormat::Text => values.collect::<Vec<_>>().join("\n"),
+        CookieFormat::Json => serde_json::to_string_pretty(
+            &values
+                .filter_map(|value| {
+                    let mut pair = value.splitn(2, '=');
+                    Some(StoredCookie {
+                        name: pair.next()?.to_owned(),
+                        value: pair.next()?.to_owned(),
+                        expires: None,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )?,
+    })
+}
+
+/// Where the cookies of the session are kept between runs
+pub trait CookieStore {
+    /// Load the stored cookies, or `None` if nothing is stored yet
+    fn load(&self) -> Result<Option<HeaderMap>, Error>;
+
+    /// Store the cookies, replacing the ones stored before
+    fn save(&self, cookies: &HeaderMap) -> Result<(), Error>;
+
+    /// Where the cookies are stored, for messages
+    fn location(&self) -> String;
+}
+
+/// Lock the file, waiting at most `timeout` while another instance holds the lock
+fn lock_with_timeout(file: &File, exclusive: bool, timeout: Duration) -> Result<(), Error> {
+    let start = Instant::now();
+    loop {
+        let result = if exclusive {
+            FileExt::try_lock_exclusive(file)
+        } else {
+            FileExt::try_lock_shared(file)
+        };
+        match result {
+            Ok(()) => return Ok(()),
+            Err(error) if error.kind() != fs2::lock_contended_error().kind() => {
+                return Err(error.into())
+            }
+            Err(_) if start.elapsed() >= timeout => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "The cookie file is locked by another instance of {} for {:?}",
+                        clap::crate_name!(),
+                        timeout
+                    ),
+                )
+                .into())
+            }
+            Err(_) => thread::sleep(Duration::from_millis(100)),
+        }
+    }
+}
+
+/// Cookies in a file such as `cookie.txt`, which is locked while it is read or written
+///
+/// The lock is taken on a separate `cookie.txt.lock`, since the cookie file itself is replaced on save.
+pub struct FileCookieStore {
+    pub path: PathBuf,
+    /// Format to save in, while both formats are loaded
+    pub format: CookieFormat,
+    pub lock_timeout: Duration,
+}
+
+impl FileCookieStore {
+    /// Path of the lock file next to the cookie file
+    fn lock_path(&self) -> PathBuf {
+        let mut name = self
+            .path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_default();
+        name.push(".lock");
+        self.path.with_file_name(name)
+    }
+
+    /// Open and lock the lock file, which is unlocked when the returned file is dropped
+    fn lock(&self, exclusive: bool) -> Result<File, Error> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(self.lock_path())?;
+        lock_with_timeout(&file, exclusive, self.lock_timeout)?;
+        Ok(file)
+    }
+}
+
+impl CookieStore for FileCookieStore {
+    fn load(&self) -> Result<Option<HeaderMap>, Error> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let _lock = self.lock(false)?;
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+        Ok(Some(parse(&buf)?))
+    }
+
+    fn save(&self, cookies: &HeaderMap) -> Result<(), Error> {
+        let path = self.path.as_path();
+        let dir = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        if !dir.exists() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = serialize(cookies, self.format)?;
+        // Held for the whole write and rename, so that instances replace the file one by one
+        let _lock = self.lock(true)?;
+        // Write a uniquely named sibling and replace the file with it,
+        // so a killed process leaves no broken cookies
+        let temp = NamedTempFile::new_in(dir)?;
+        let mut writer = BufWriter::new(temp.as_file());
+        writer.write_all(contents.as_bytes())?;
+        writer.into_inner().map_err(io::Error::from)?.sync_all()?;
+        if let Err(error) = temp.persist(path) {
+            if !crosses_devices(&error.error) {
+                return Err(error.error.into());
+            }
+            // rename fails across filesystems, where the file has to be copied
+            debug!("falling back to copying {}", error.file.path().display());
+            fs::copy(error.file.path(), path)?;
+        }
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+/// Service name of the cookies in the OS keychain
+pub const KEYCHAIN_SERVICE: &str = "atcoder4rust";
+
+/// Account name of the cookies in the OS keychain
+pub const KEYCHAIN_ACCOUNT: &str = "atcoder";
+
+/// Cookies in the OS keychain (e.g. Keychain of macOS, Credential Manager of Windows,
+/// Secret Service of Linux), stored in the text format
+#[derive(Default)]
+pub struct KeychainCookieStore;
+
+/// Errors of the keychain are regarded as I/O errors, since it is a storage like the file
+fn keychain_error(error: KeyringError) -> Error {
+    io::Error::new(io::ErrorKind::Other, format!("Keychain: {}", error)).into()
+}
+
+impl CookieStore for KeychainCookieStore {
+    fn load(&self) -> Result<Option<HeaderMap>, Error> {
+        match Keyring::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).get_password() {
+            Ok(contents) => Ok(Some(parse(&contents)?)),
+            Err(KeyringError::NoPasswordFound) => Ok(None),
+            Err(error) => Err(keychain_error(error)),
+        }
+    }
+
+    fn save(&self, cookies: &HeaderMap) -> Result<(), Error> {
+        Keyring::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+            .set_password(&serialize(cookies, CookieFormat::Text)?)
+            .map_err(keychain_error)
+    }
+
+    fn location(&self) -> String {
+        format!("the keychain ({}/{})", KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path) -> FileCookieStore {
+        FileCookieStore {
+            path: dir.join("cookie.txt"),
+            format: CookieFormat::Text,
+            lock_timeout: Duration::from_secs(1),
+        }
+    }
+
+    fn cookies(value: &str) -> HeaderMap {
+        let mut cookies = HeaderMap::new();
+        cookies.append(header::COOKIE, HeaderValue::from_str(value).unwrap());
+        cookies
+    }
+
+    #[test]
+    fn save_replaces_the_file_and_leaves_no_temporary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.save(&cookies("REVEL_SESSION=old")).unwrap();
+        store.save(&cookies("REVEL_SESSION=new")).unwrap();
+
+        assert_eq!(store.load().unwrap().unwrap(), cookies("REVEL_SESSION=new"));
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["cookie.txt", "cookie.txt.lock"]);
+    }
+
+    #[test]
+    fn save_waits_for_the_lock_of_another_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCookieStore {
+            lock_timeout: Duration::from_millis(200),
+            ..store(dir.path())
+        };
+        let lock = store.lock(true).unwrap();
+
+        assert!(store.save(&cookies("REVEL_SESSION=new")).is_err());
+        assert!(!store.path.exists());
+        drop(lock);
+        store.save(&cookies("REVEL_SESSION=new")).unwrap();
+        assert!(store.path.exists());
+    }
+}
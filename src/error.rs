@@ -1,34 +1,90 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
 
 use reqwest::StatusCode;
 
 #[derive(Debug)]
 pub enum Error {
+    /// Failures of login (e.g. wrong credentials)
+    Auth(String),
     /// HTTP status code
     Http(StatusCode),
     /// Invalid states
     Invalid(String),
+    /// Pages or selectors which could not be parsed
+    Parse(String),
+    /// 429 Too Many Requests which remained after retrying, with the wait which AtCoder asked for
+    RateLimit(Duration),
     /// `std::io::Error`
     Io(std::io::Error),
+    /// `serde_json::Error`
+    Json(serde_json::Error),
+    /// `reqwest::Error` which remained after retrying
+    Network {
+        /// The number of retries which were made before giving up
+        retries: usize,
+        /// The error of the last attempt
+        last: reqwest::Error,
+    },
     /// `reqwest::Error`
     Reqwest(reqwest::Error),
+    /// `tokio::task::JoinError` of a spawned task which panicked or was cancelled
+    Task(tokio::task::JoinError),
+    /// `toml::de::Error`
+    Toml(toml::de::Error),
     /// `url::ParseError`
     Url(url::ParseError),
+    /// `std::str::Utf8Error` (e.g. from `percent_encoding::PercentDecode::decode_utf8`)
+    Utf8(std::str::Utf8Error),
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut Formatter) -> std::fmt::Result {
         match self {
+            Error::Auth(msg) => write!(formatter, "Authentication failed: {}", msg),
             Error::Http(status) => write!(formatter, "{}", status),
             Error::Io(e) => write!(formatter, "{}", e),
+            Error::Json(e) => write!(formatter, "{}", e),
+            Error::Network { retries, last } => {
+                write!(formatter, "{} (after {} retries)", last, retries)
+            }
             Error::Reqwest(e) => write!(formatter, "{}", e),
+            Error::Task(e) => write!(formatter, "async task failed: {}", e),
+            Error::Toml(e) => write!(formatter, "{}", e),
             Error::Url(e) => write!(formatter, "{}", e),
+            Error::Utf8(e) => write!(formatter, "{}", e),
             Error::Invalid(msg) => write!(formatter, "Invalid: {}", msg),
+            Error::Parse(msg) => write!(formatter, "Parse error: {}", msg),
+            Error::RateLimit(wait) => write!(
+                formatter,
+                "Too many requests; retry after {}s",
+                wait.as_secs()
+            ),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Auth(_)
+            | Error::Http(_)
+            | Error::Invalid(_)
+            | Error::Parse(_)
+            | Error::RateLimit(_) => None,
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Network { last, .. } => Some(last),
+            Error::Reqwest(e) => Some(e),
+            Error::Task(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            Error::Url(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+        }
+    }
+}
 
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
@@ -36,14 +92,38 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Self {
         Self::Reqwest(error)
     }
 }
 
+impl From<tokio::task::JoinError> for Error {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::Task(error)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Toml(error)
+    }
+}
+
 impl From<url::ParseError> for Error {
     fn from(error: url::ParseError) -> Self {
         Self::Url(error)
     }
 }
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(error: std::str::Utf8Error) -> Self {
+        Self::Utf8(error)
+    }
+}
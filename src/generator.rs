@@ -1,36 +1,967 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+use toml_edit::Document;
+
+use crate::{Error, Sample, Task};
+
+// Only copied into generated tests as source
+#[allow(dead_code)]
+mod compare;
+
+/// Source of the functions which generated tests compare outputs with
+const COMPARE_RS: &str = include_str!("generator/compare.rs");
+
+/// Crates which generated tests use as their harness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestFramework {
+    /// `assert_cmd` + `predicates`
+    AssertCmd,
+    /// `cli_test_dir` (deprecated)
+    CliTestDir,
+}
+
+impl TestFramework {
+    /// Names of the frameworks accepted by `from_name`
+    pub const NAMES: [&'static str; 2] = ["assert_cmd", "cli_test_dir"];
+
+    /// Get the framework from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "assert_cmd" => Some(Self::AssertCmd),
+            "cli_test_dir" => Some(Self::CliTestDir),
+            _ => None,
+        }
+    }
+
+    /// Name of the framework, which `from_name` accepts
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AssertCmd => "assert_cmd",
+            Self::CliTestDir => "cli_test_dir",
+        }
+    }
+
+    /// `[dev-dependencies]` which the framework needs
+    pub fn dev_dependencies(&self) -> &'static str {
+        match self {
+            Self::AssertCmd => "assert_cmd = \"1.0\"\npredicates = \"1.0\"",
+            Self::CliTestDir => "cli_test_dir = \"0.1\"",
+        }
+    }
+}
+
+impl Default for TestFramework {
+    fn default() -> Self {
+        Self::AssertCmd
+    }
+}
+
+/// How generated tests compare the output with the expected one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Byte-exact comparison
+    Exact,
+    /// Ignore the case of letters
+    CaseInsensitive,
+    /// Ignore leading and trailing whitespaces of each line
+    Trim,
+    /// `Trim` and `CaseInsensitive`
+    TrimCaseInsensitive,
+    /// Ignore the order of whitespace-separated tokens
+    Multiset,
+    /// Ignore the order of whitespace-separated tokens in each line
+    MultisetLines,
+}
+
+impl CompareMode {
+    /// Names of the modes accepted by `from_name`
+    pub const NAMES: [&'static str; 6] = [
+        "exact",
+        "case-insensitive",
+        "trim",
+        "trim-case-insensitive",
+        "multiset",
+        "multiset-lines",
+    ];
+
+    /// Get the mode from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exact" => Some(Self::Exact),
+            "case-insensitive" => Some(Self::CaseInsensitive),
+            "trim" => Some(Self::Trim),
+            "trim-case-insensitive" => Some(Self::TrimCaseInsensitive),
+            "multiset" => Some(Self::Multiset),
+            "multiset-lines" => Some(Self::MultisetLines),
+            _ => None,
+        }
+    }
+
+    /// Name of the mode, which `from_name` accepts
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::CaseInsensitive => "case-insensitive",
+            Self::Trim => "trim",
+            Self::TrimCaseInsensitive => "trim-case-insensitive",
+            Self::Multiset => "multiset",
+            Self::MultisetLines => "multiset-lines",
+        }
+    }
+
+    /// Name of the function in `compare.rs` which normalizes the text before the comparison
+    fn normalizer(&self) -> Option<&'static str> {
+        match self {
+            Self::Exact => None,
+            Self::CaseInsensitive => Some("case_insensitive"),
+            Self::Trim => Some("trim"),
+            Self::TrimCaseInsensitive => Some("trim_case_insensitive"),
+            Self::Multiset => Some("multiset"),
+            Self::MultisetLines => Some("multiset_lines"),
+        }
+    }
+}
+
+impl Default for CompareMode {
+    fn default() -> Self {
+        Self::Exact
+    }
+}
+
+/// Options of generated tests
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestOptions {
+    /// Crate which tests use as their harness
+    pub framework: TestFramework,
+    /// How to compare the output with the expected one
+    pub compare_mode: CompareMode,
+    /// If given, outputs are compared token by token as floats with this tolerance instead
+    pub float_tolerance: Option<f64>,
+    /// Emit ignored stubs, since samples of interactive tasks cannot be fed at once
+    pub interactive: bool,
+}
+
+impl TestOptions {
+    /// Whether tests can compare outputs byte by byte
+    fn is_exact(&self) -> bool {
+        self.compare_mode == CompareMode::Exact && self.float_tolerance.is_none()
+    }
+}
+
+/// Version of proconio which AtCoder provides
+pub const DEFAULT_PROCONIO_VERSION: &str = "=0.3.6";
+
+/// Generate the dependency on proconio of the version as a line of Cargo.toml
+pub fn generate_proconio_dependency(version: &str) -> String {
+    format!(
+        r#"proconio = {{ version = "{}", features = ["derive"] }}"#,
+        version
+    )
+}
+
+/// Editions which generated crates can use
+pub const EDITIONS: [&str; 3] = ["2015", "2018", "2021"];
+
+/// Edition of generated crates by default
+pub const DEFAULT_EDITION: &str = "2021";
+
+/// `resolver = "2"` line for the edition, which is the default of 2021 but has to be explicit
+fn resolver_line(edition: &str) -> &'static str {
+    if edition == "2021" {
+        "resolver = \"2\"\n"
+    } else {
+        ""
+    }
+}
+
+/// `[profile.release]` for fast solutions at the cost of slow release builds
+const OPTIMIZED_RELEASE_PROFILE: &str = r#"
+[profile.release]
+opt-level = 3
+lto = "fat"
+codegen-units = 1
+panic = "abort"
+"#;
+
+/// Escape `"` and `\` so that `text` can be put in a basic string of TOML
+fn escape_toml_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 /// Generate Cargo.toml as a String
-pub fn generate_cargo_toml(project_name: &str, author: Option<&str>, dependencies: &str) -> String {
+///
+/// If `optimize` is true, `[profile.release]` is tuned for runtime speed.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::generator;
+///
+/// let parse = |cargo_toml: &str| cargo_toml.parse::<toml::Value>().unwrap();
+///
+/// let cargo_toml = generator::generate_cargo_toml("abc001", None, "", "", "2018", false);
+/// assert_eq!(parse(&cargo_toml)["package"]["name"].as_str(), Some("abc001"));
+///
+/// // Quotes in the author are escaped
+/// let cargo_toml = generator::generate_cargo_toml(
+///     "abc001",
+///     Some(r#"kbone "\o/" <kbone@example.com>"#),
+///     "",
+///     "",
+///     "2018",
+///     false,
+/// );
+/// assert_eq!(
+///     parse(&cargo_toml)["package"]["authors"][0].as_str(),
+///     Some(r#"kbone "\o/" <kbone@example.com>"#)
+/// );
+///
+/// let cargo_toml = generator::generate_cargo_toml(
+///     "abc001",
+///     None,
+///     "proconio = \"=0.3.6\"\nnum = \"0.2\"",
+///     "assert_cmd = \"1.0\"",
+///     "2021",
+///     true,
+/// );
+/// let manifest = parse(&cargo_toml);
+/// assert_eq!(manifest["dependencies"]["num"].as_str(), Some("0.2"));
+/// assert_eq!(manifest["profile"]["release"]["lto"].as_str(), Some("fat"));
+/// ```
+pub fn generate_cargo_toml(
+    project_name: &str,
+    author: Option<&str>,
+    dependencies: &str,
+    dev_dependencies: &str,
+    edition: &str,
+    optimize: bool,
+) -> String {
+    generate_cargo_toml_with_bins(
+        project_name,
+        author,
+        &[(project_name, "src/main.rs")],
+        None,
+        dependencies,
+        dev_dependencies,
+        edition,
+        optimize,
+    )
+}
+
+/// Generate Cargo.toml of a member of the workspace which `generate_workspace_toml` generates
+///
+/// `resolver` is only read from the root of a workspace, and Cargo warns about it in members.
+pub fn generate_workspace_member_cargo_toml(
+    project_name: &str,
+    author: Option<&str>,
+    dependencies: &str,
+    dev_dependencies: &str,
+    edition: &str,
+) -> String {
+    let cargo_toml = generate_cargo_toml(
+        project_name,
+        author,
+        dependencies,
+        dev_dependencies,
+        edition,
+        false,
+    );
+    match resolver_line(edition) {
+        "" => cargo_toml,
+        resolver => cargo_toml.replacen(resolver, "", 1),
+    }
+}
+
+/// Generate Cargo.toml as a String which has a `[[bin]]` entry for each pair of (name, path),
+/// and a `[lib]` entry if `lib_path` is given
+pub fn generate_cargo_toml_with_bins(
+    project_name: &str,
+    author: Option<&str>,
+    bins: &[(&str, &str)],
+    lib_path: Option<&str>,
+    dependencies: &str,
+    dev_dependencies: &str,
+    edition: &str,
+    optimize: bool,
+) -> String {
+    let bins: String = bins
+        .iter()
+        .map(|(name, path)| format!("[[bin]]\nname = \"{}\"\npath = \"{}\"\n\n", name, path))
+        .collect();
+    let lib = lib_path
+        .map(|path| format!("[lib]\npath = \"{}\"\n\n", path))
+        .unwrap_or_default();
     format!(
         r#"[package]
 name = "{name}"
 version = "0.1.0"
 authors = ["{author}"]
-edition = "2018"
+edition = "{edition}"
+{resolver}
+{lib}{bins}[dependencies]
+{dependencies}
 
-[[bin]]
-name = "{name}"
-path = "src/main.rs"
+[dev-dependencies]
+{dev_dependencies}
+{profile}"#,
+        name = project_name,
+        author = escape_toml_string(author.unwrap_or_default()),
+        edition = edition,
+        resolver = resolver_line(edition),
+        lib = lib,
+        bins = bins,
+        dependencies = dependencies,
+        dev_dependencies = dev_dependencies,
+        profile = if optimize {
+            OPTIMIZED_RELEASE_PROFILE
+        } else {
+            ""
+        }
+    )
+}
 
-[dependencies]
-{dependencies}
+/// Generate an HTML page as a String which shows the statement of the task offline
+pub fn generate_statement_html(title: Option<&str>, statement: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+</head>
+<body>
+{statement}
+</body>
+</html>
 "#,
-        name = project_name,
-        author = author.unwrap_or_default(),
-        dependencies = dependencies
+        title = title.unwrap_or_default(),
+        statement = statement
+    )
+}
+
+/// Generate a comment line as a String which describes the limits of the task
+///
+/// An empty String is returned if neither limit is known.
+pub fn generate_limits_comment(time_limit: Option<&str>, memory_limit: Option<&str>) -> String {
+    let limits: Vec<_> = time_limit
+        .map(|limit| format!("Time Limit: {}", limit))
+        .into_iter()
+        .chain(memory_limit.map(|limit| format!("Memory Limit: {}", limit)))
+        .collect();
+    if limits.is_empty() {
+        String::new()
+    } else {
+        format!("// {}\n", limits.join(" | "))
+    }
+}
+
+/// Generate .gitignore as a String
+pub fn generate_gitignore(include_lock: bool) -> String {
+    let mut gitignore = "/target\ncookie.txt\n".to_owned();
+    if include_lock {
+        gitignore.push_str("Cargo.lock\n");
+    }
+    gitignore
+}
+
+/// Generate .devcontainer/devcontainer.json as a String for GitHub Codespaces
+///
+/// `rust_version` is a tag of the `mcr.microsoft.com/devcontainers/rust` image (e.g. `latest` or `1`).
+pub fn generate_devcontainer(rust_version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "Rust",
+  "image": "mcr.microsoft.com/devcontainers/rust:{rust_version}",
+  "customizations": {{
+    "vscode": {{
+      "extensions": [
+        "rust-lang.rust-analyzer",
+        "tamasfe.even-better-toml"
+      ]
+    }}
+  }},
+  "postCreateCommand": "cargo check"
+}}
+"#,
+        rust_version = rust_version
     )
 }
 
+/// Generate a justfile as a String which has recipes to test, check, run and submit the tasks
+///
+/// The recipes work in every layout, since `test` and `submit` are delegated to `atcoder4rust`
+/// and `run` looks for the task's member or binary before falling back to the modules.
+pub fn generate_justfile(contest_id: &str, tasks: &[&str]) -> String {
+    format!(
+        r#"# Tasks of {contest_id}: {tasks}
+
+# Run the sample tests of the task, or all of them
+test task="":
+    atcoder4rust test {{{{task}}}}
+
+# Check that every task compiles
+check:
+    cargo check --all-targets
+
+# Run the solution of the task with the input file
+run task input:
+    #!/usr/bin/env sh
+    if [ -d tasks/{{{{task}}}} ]; then
+        cargo run -q --package {{{{task}}}} < {{{{input}}}}
+    elif [ -f src/bin/{{{{task}}}}.rs ]; then
+        cargo run -q --bin {{{{task}}}} < {{{{input}}}}
+    else
+        cargo run -q -- {{{{task}}}} < {{{{input}}}}
+    fi
+
+# Submit the solution of the task
+submit task:
+    atcoder4rust --root .. submit {contest_id} {{{{task}}}}
+"#,
+        contest_id = contest_id,
+        tasks = tasks.join(", ")
+    )
+}
+
+/// Generate PROBLEMS.md as a String, which lists the titles and the scores of the tasks
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::{generator, Task};
+///
+/// let mut task = Task::default();
+/// task.title = Some("A - Two Integers".to_owned());
+/// task.meta.score = Some(100);
+/// let problems_md =
+///     generator::generate_problems_md("abc001", &[("A", &task), ("B", &Task::default())]);
+/// assert!(problems_md.starts_with("# abc001\n"));
+/// assert!(problems_md.contains("| A | A - Two Integers | 100 |\n"));
+/// assert!(problems_md.contains("| B |  | - |\n"));
+/// ```
+pub fn generate_problems_md(contest_id: &str, tasks: &[(&str, &Task)]) -> String {
+    let rows: String = tasks
+        .iter()
+        .map(|(name, task)| {
+            format!(
+                "| {} | {} | {} |\n",
+                name,
+                // A bar would end the cell
+                task.title
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace('|', "\\|"),
+                task.meta
+                    .score
+                    .map_or_else(|| "-".to_owned(), |score| score.to_string())
+            )
+        })
+        .collect();
+    format!(
+        "# {}\n\n| Task | Title | Score |\n| --- | --- | ---: |\n{}",
+        contest_id, rows
+    )
+}
+
+/// `src/lib.rs` of `--shared-lib`, which has common utilities without external crates
+pub const SHARED_LIB: &str = r#"//! Utilities shared by the tasks
+
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Modulus of `ModInt`
+pub const MOD: u64 = 998_244_353;
+
+/// Integer modulo `MOD`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModInt(pub u64);
+
+impl ModInt {
+    pub fn new(value: i64) -> Self {
+        Self(value.rem_euclid(MOD as i64) as u64)
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let (mut base, mut result) = (self, Self(1));
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Inverse by Fermat's little theorem, since `MOD` is prime
+    pub fn inv(self) -> Self {
+        self.pow(MOD - 2)
+    }
+}
+
+impl Add for ModInt {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self((self.0 + rhs.0) % MOD)
+    }
+}
+
+impl AddAssign for ModInt {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for ModInt {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self((self.0 + MOD - rhs.0) % MOD)
+    }
+}
+
+impl SubAssign for ModInt {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for ModInt {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0 * rhs.0 % MOD)
+    }
+}
+
+impl MulAssign for ModInt {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for ModInt {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(0) - self
+    }
+}
+
+impl std::fmt::Display for ModInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+/// Disjoint sets with path compression and union by size
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parents: (0..n).collect(),
+            sizes: vec![1; n],
+        }
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        if self.parents[x] != x {
+            self.parents[x] = self.find(self.parents[x]);
+        }
+        self.parents[x]
+    }
+
+    /// Merge the sets of `x` and `y`, and return whether they were different
+    pub fn unite(&mut self, x: usize, y: usize) -> bool {
+        let (mut x, mut y) = (self.find(x), self.find(y));
+        if x == y {
+            return false;
+        }
+        if self.sizes[x] < self.sizes[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parents[y] = x;
+        self.sizes[x] += self.sizes[y];
+        true
+    }
+
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+}
+
+/// Binary indexed tree of prefix sums over 0-indexed positions
+pub struct FenwickTree<T> {
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Default + AddAssign> FenwickTree<T> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![T::default(); n + 1],
+        }
+    }
+
+    pub fn add(&mut self, index: usize, value: T) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += value;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of `[0, end)`
+    pub fn sum(&self, end: usize) -> T {
+        let mut result = T::default();
+        let mut i = end;
+        while i > 0 {
+            result += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        result
+    }
+}
+"#;
+
+/// Inner attribute at the top of each task file, which silences the warnings of the template
+/// until the solution is written
+pub const ALLOW_ATTRS: &str = "#![allow(dead_code, unused_imports)]\n\n";
+
+/// Generate the line which imports `SHARED_LIB` of the crate into a task file
+pub fn generate_shared_lib_use(project_name: &str) -> String {
+    format!(
+        "#[allow(unused_imports)]\nuse {}::*;\n\n",
+        project_name.replace('-', "_")
+    )
+}
+
+/// Target of the judge of AtCoder
+pub const JUDGE_TARGET: &str = "x86_64-unknown-linux-gnu";
+
+/// `opt-level` of the dev profile by default, which makes tests much faster with little build time
+pub const DEFAULT_DEV_OPT_LEVEL: u8 = 1;
+
+/// Generate .cargo/config.toml as a String
+pub fn generate_cargo_config(target: Option<&str>, dev_opt_level: u8) -> String {
+    let build = target
+        .map(|target| format!("[build]\ntarget = \"{}\"\n\n", target))
+        .unwrap_or_default();
+    format!("{}[profile.dev]\nopt-level = {}\n", build, dev_opt_level)
+}
+
+/// Generate a GitHub Actions workflow as a String which runs `cargo test` with the toolchain
+pub fn generate_github_actions_workflow(contest_id: &str, rust_version: &str) -> String {
+    format!(
+        r#"name: Test {contest_id}
+
+on: [push, pull_request]
+
+jobs:
+  test_{job}:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v2
+      - uses: actions-rs/toolchain@v1
+        with:
+          toolchain: {rust_version}
+          profile: minimal
+          override: true
+      - uses: actions/cache@v2
+        with:
+          path: |
+            ~/.cargo
+            target
+          key: ${{{{ runner.os }}}}-cargo-{job}-${{{{ hashFiles('**/Cargo.toml') }}}}
+      - run: cargo test
+"#,
+        contest_id = contest_id,
+        job = sanitize_module_name(contest_id).trim_start_matches('_'),
+        rust_version = rust_version
+    )
+}
+
+/// Generate the virtual manifest of a workspace as a String
+///
+/// `shared_deps` becomes `[workspace.dependencies]`, which members inherit by `{ workspace = true }`.
+///
+/// Profiles are only read from the root of a workspace, so `optimize` is given here.
+pub fn generate_workspace_toml(
+    members: &[&str],
+    shared_deps: &str,
+    edition: &str,
+    optimize: bool,
+) -> String {
+    let members: String = members
+        .iter()
+        .map(|member| format!("    \"{}\",\n", member))
+        .collect();
+    // A virtual manifest must not have `[dependencies]`, so tables of dependencies are moved too
+    let shared_deps = shared_deps
+        .split('\n')
+        .map(|line| {
+            ["[dependencies.", "[dev-dependencies."]
+                .iter()
+                .find_map(|header| line.trim_start().strip_prefix(header))
+                .map_or_else(
+                    || line.to_owned(),
+                    |name| format!("[workspace.dependencies.{}", name),
+                )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        r#"[workspace]
+{resolver}members = [
+{members}]
+
+[workspace.dependencies]
+{shared_deps}
+{profile}"#,
+        resolver = resolver_line(edition),
+        members = members,
+        shared_deps = shared_deps,
+        profile = if optimize {
+            OPTIMIZED_RELEASE_PROFILE
+        } else {
+            ""
+        }
+    )
+}
+
+/// Convert dependencies written in Cargo.toml format to the ones inherited from the workspace
+///
+/// Dependencies may span lines (e.g. an array of features), or be tables such as
+/// `[dependencies.proconio]`.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::generator;
+///
+/// let dependencies = r#"proconio = { version = "0.3.6", features = [
+///     "derive",
+/// ] }
+///
+/// [dependencies.num]
+/// version = "0.2.1"
+/// "#;
+/// assert_eq!(
+///     generator::generate_workspace_member_dependencies(dependencies).unwrap(),
+///     "proconio = { workspace = true }\nnum = { workspace = true }"
+/// );
+/// ```
+pub fn generate_workspace_member_dependencies(dependencies: &str) -> Result<String, Error> {
+    let document = dependencies
+        .parse::<Document>()
+        .map_err(|error| Error::Invalid(format!("Invalid dependencies: {}", error)))?;
+    let mut names = Vec::new();
+    for (key, item) in document.as_table().iter() {
+        match item.as_table() {
+            // The names are in the headers, e.g. `[dependencies.proconio]`
+            Some(table) if key == "dependencies" || key == "dev-dependencies" => {
+                names.extend(table.iter().map(|(name, _)| name))
+            }
+            _ => names.push(key),
+        }
+    }
+    Ok(names
+        .iter()
+        .map(|name| format!("{} = {{ workspace = true }}", name))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Keywords which have to be written as raw identifiers (e.g. `r#as`) to be module names
+const KEYWORDS: [&str; 47] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Keywords which cannot be raw identifiers
+const NON_RAW_KEYWORDS: [&str; 3] = ["crate", "self", "super"];
+
+/// Convert a task name to the name of its module file
+///
+/// The name is lowercased, hyphens and spaces are replaced with underscores,
+/// and `_` is prepended if it starts with a digit (e.g. `1A` becomes `_1a`).
+pub fn sanitize_module_name(task_name: &str) -> String {
+    let name: String = task_name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c == '-' || c.is_whitespace() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || NON_RAW_KEYWORDS.contains(&name.as_str())
+    {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Convert a task name to the identifier of its module, using raw-identifier syntax for keywords
+pub fn module_identifier(task_name: &str) -> String {
+    let name = sanitize_module_name(task_name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Replace `{{KEY}}` in the template with the value of KEY in `vars`
+///
+/// Placeholders of unknown keys are left as they are, and returned as the second element.
+/// Only alphanumerics and underscores make a key, so `{{}}` in format strings is kept silently.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::generator;
+///
+/// let (source, unknown) = generator::substitute_template_vars(
+///     "// {{AUTHOR}}\n// {{DATE}}\nfn main() {}\n",
+///     &[("AUTHOR", "kbone")],
+/// );
+/// assert_eq!(source, "// kbone\n// {{DATE}}\nfn main() {}\n");
+/// assert_eq!(unknown, vec!["DATE".to_owned()]);
+///
+/// let (source, unknown) = generator::substitute_template_vars(r#"println!("{{}}", x);"#, &[]);
+/// assert_eq!(source, r#"println!("{{}}", x);"#);
+/// assert!(unknown.is_empty());
+/// ```
+pub fn substitute_template_vars(template: &str, vars: &[(&str, &str)]) -> (String, Vec<String>) {
+    let mut source = String::with_capacity(template.len());
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start + 2..].find("}}") {
+            Some(end) => start + 2 + end,
+            None => break,
+        };
+        let key = &rest[start + 2..end];
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            source.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        }
+        source.push_str(&rest[..start]);
+        match vars.iter().find(|(name, _)| *name == key) {
+            Some((_, value)) => source.push_str(value),
+            None => {
+                if !unknown.iter().any(|name| name == key) {
+                    unknown.push(key.to_owned());
+                }
+                source.push_str(&rest[start..end + 2]);
+            }
+        }
+        rest = &rest[end + 2..];
+    }
+    source.push_str(rest);
+    (source, unknown)
+}
+
+/// Order of tasks in the generated files
+///
+/// Numbers are compared as numbers, and "Ex" of ABC230 through ABC318 is placed where "H" would be.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::generator;
+///
+/// let mut tasks = vec!["ex", "a10", "g", "a2"];
+/// tasks.sort_by(|a, b| generator::compare_task_names(a, b));
+/// assert_eq!(tasks, ["a2", "a10", "g", "ex"]);
+/// ```
+pub fn compare_task_names(a: &str, b: &str) -> Ordering {
+    fn key(name: &str) -> &str {
+        if name.eq_ignore_ascii_case("ex") {
+            "h"
+        } else {
+            name
+        }
+    }
+    natord::compare_ignore_case(key(a), key(b)).then_with(|| a.cmp(b))
+}
+
 /// Generate main.rs as a String
+///
+/// The argument is compared case-insensitively, so both `1A` and `1a` run the module `_1a`.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::generator;
+///
+/// // Tasks are sorted
+/// let main_rs = generator::generate_main_rs(vec!["b".to_owned(), "a".to_owned()]);
+/// assert!(main_rs.starts_with("mod a;\nmod b;\n"));
+/// let arm_a = main_rs.find(r#""a" => a::main(),"#).unwrap();
+/// let arm_b = main_rs.find(r#""b" => b::main(),"#).unwrap();
+/// assert!(arm_a < arm_b);
+///
+/// // Names which are not identifiers are sanitized only in the module
+/// let main_rs = generator::generate_main_rs(vec!["1A".to_owned(), "fn".to_owned()]);
+/// assert!(main_rs.contains("mod _1a;"));
+/// assert!(main_rs.contains(r#""1a" => _1a::main(),"#));
+/// assert!(main_rs.contains(r#""fn" => r#fn::main(),"#));
+///
+/// // Numbers in names are compared as numbers
+/// let main_rs = generator::generate_main_rs(vec!["a10".to_owned(), "a2".to_owned()]);
+/// assert!(main_rs.starts_with("mod a2;\nmod a10;\n"));
+///
+/// // Without tasks, only the fallback arm remains
+/// let main_rs = generator::generate_main_rs(Vec::new());
+/// assert!(main_rs.contains("_ => {},"));
+/// assert!(!main_rs.contains("mod "));
+/// ```
 pub fn generate_main_rs(task_names: Vec<String>) -> String {
     let mut task_names = task_names;
-    task_names.sort();
+    task_names.sort_by(|a, b| compare_task_names(a, b));
     let mods: String = task_names
         .iter()
-        .map(|task| format!("mod {};\n", task))
+        .map(|task| format!("mod {};\n", module_identifier(task)))
         .collect();
     let matches = task_names
         .iter()
-        .map(|task| format!(r#"        "{task}" => {task}::main(),"#, task = task))
+        .map(|task| {
+            format!(
+                r#"        "{name}" => {module}::main(),"#,
+                name = task.to_lowercase(),
+                module = module_identifier(task)
+            )
+        })
         .collect::<Vec<_>>()
         .join("\n");
     format!(
@@ -40,7 +971,7 @@ fn main() {{
     if args.len() < 2 {{
         return;
     }}
-    match args.nth(1).unwrap().as_str() {{
+    match args.nth(1).unwrap().to_lowercase().as_str() {{
 {matches}
         _ => {{}},
     }}
@@ -51,22 +982,125 @@ fn main() {{
     )
 }
 
+/// The minimum number of `#` (at least 1) with which `text` can be written as a raw string literal
+///
+/// e.g. 2 for `r#"..."#`, which contains `"#`.
+fn min_raw_delimiters(text: &str) -> usize {
+    // A longer run of `#` than any in the text always closes only at the end
+    (1..)
+        .find(|&depth| !text.contains(&format!("\"{}", "#".repeat(depth))))
+        .unwrap()
+}
+
+/// Write `text` as a raw string literal
+fn raw_string_literal(text: &str) -> String {
+    let hashes = "#".repeat(min_raw_delimiters(text));
+    format!("r{}\"{}\"{}", hashes, text, hashes)
+}
+
+/// Source of the function in `compare.rs`, indented to be put in the body of a generated test
+fn compare_function(name: &str) -> String {
+    let start = COMPARE_RS
+        .find(&format!("pub fn {}(", name))
+        .unwrap_or_else(|| panic!("{} is not in compare.rs", name));
+    let end = start
+        + COMPARE_RS[start..]
+            .find("\n}\n")
+            .unwrap_or_else(|| panic!("{} is not closed in compare.rs", name))
+        + "\n}\n".len();
+    COMPARE_RS[start + "pub ".len()..end]
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_owned()
+            } else {
+                format!("        {}\n", line)
+            }
+        })
+        .collect()
+}
+
+/// Generate statements which compare `stdout` with the expected output
+fn generate_assertion(options: &TestOptions, output: &str) -> String {
+    if let Some(tolerance) = options.float_tolerance {
+        format!(
+            "{function}        assert_close(&stdout, {output}, {tolerance:?});\n",
+            function = compare_function("assert_close"),
+            output = raw_string_literal(output),
+            tolerance = tolerance
+        )
+    } else if let Some(normalizer) = options.compare_mode.normalizer() {
+        format!(
+            "{function}        assert_eq!({name}(&stdout), {name}({output}));\n",
+            function = compare_function(normalizer),
+            name = normalizer,
+            output = raw_string_literal(output)
+        )
+    } else {
+        format!(
+            "        assert_eq!(stdout, {});\n",
+            raw_string_literal(output)
+        )
+    }
+}
+
 /// Generate a test as a String which check that the function passes this sample case
 pub fn generate_sample(
+    options: &TestOptions,
     project_name: &str,
     module_name: &str,
     sample_name: &str,
     input: &str,
     output: &str,
 ) -> String {
-    format!(
-        r##"    #[test]
+    if options.interactive {
+        return format!(
+            r#"    #[test]
+    #[ignore] // interactive problem
+    fn {sample_name}() {{
+        // Input and output are exchanged in multiple rounds, so check this sample by hand
+    }}
+"#,
+            sample_name = sample_name
+        );
+    }
+    match options.framework {
+        TestFramework::AssertCmd => format!(
+            r##"    #[test]
+    fn {sample_name}() {{
+        let assert = Command::cargo_bin("{project_name}")
+            .unwrap()
+            .arg("{module_name}")
+            .write_stdin({input})
+            .assert()
+            .success()
+            .stderr(predicate::str::is_empty());
+{assertion}    }}
+"##,
+            project_name = project_name,
+            sample_name = sample_name,
+            module_name = module_name,
+            input = raw_string_literal(input),
+            assertion = if options.is_exact() {
+                format!(
+                    "        assert.stdout(predicate::str::diff({}));\n",
+                    raw_string_literal(output)
+                )
+            } else {
+                format!(
+                    "        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).into_owned();\n{}",
+                    generate_assertion(options, output)
+                )
+            }
+        ),
+        TestFramework::CliTestDir => format!(
+            r##"    #[test]
     fn {sample_name}() {{
         let test_dir = TestDir::new("./{project_name}", "");
         let output = test_dir
             .cmd()
             .arg("{module_name}")
-            .output_with_stdin(r#"{input}"#)
+            .output_with_stdin({input})
             .expect_success();
         let stderr = output.stderr_str();
         if !stderr.is_empty() {{
@@ -74,29 +1108,116 @@ pub fn generate_sample(
             eprint!("{{}}", stderr);
             eprintln!("==============");
         }}
-        assert_eq!(output.stdout_str(), r#"{output}"#);
-        assert!(stderr.is_empty(), "stderr is not empty");
+        let stdout = output.stdout_str();
+{assertion}        assert!(stderr.is_empty(), "stderr is not empty");
     }}
 "##,
-        project_name = project_name,
-        sample_name = sample_name,
-        module_name = module_name,
-        input = input,
-        output = output
-    )
+            project_name = project_name,
+            sample_name = sample_name,
+            module_name = module_name,
+            input = raw_string_literal(input),
+            assertion = generate_assertion(options, output)
+        ),
+    }
+}
+
+/// Extract the samples from the last `#[cfg(test)]` block which `generate_test_cases` generated
+///
+/// Raw string literals in the block are read in pairs of input and output,
+/// so interactive stubs, which have no literals, yield no samples.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::{
+///     generator::{self, CompareMode, TestFramework, TestOptions},
+///     Sample,
+/// };
+///
+/// let options = TestOptions {
+///     framework: TestFramework::AssertCmd,
+///     compare_mode: CompareMode::Exact,
+///     float_tolerance: None,
+///     interactive: false,
+/// };
+/// let samples = vec![Sample {
+///     input: "1 \"2\"#\n".to_owned(),
+///     output: "3\n".to_owned(),
+/// }];
+/// let tests = generator::generate_test_cases(&options, "abc001", "a", &samples);
+/// assert_eq!(generator::extract_samples(&tests), samples);
+/// ```
+pub fn extract_samples(source: &str) -> Vec<Sample> {
+    let tests = source
+        .rfind("#[cfg(test)]")
+        .map_or("", |index| &source[index..]);
+    // Regex has no backreference, so the closing delimiter is searched by hand.
+    // The pattern is constant, so it panics only if the literal is mistyped.
+    let opening = Regex::new(r##"\br(#*)""##).expect("regex literal is invalid");
+    let mut literals = Vec::new();
+    let mut rest = tests;
+    while let Some(captures) = opening.captures(rest) {
+        let closing = format!("\"{}", &captures[1]);
+        let body = &rest[captures.get(0).unwrap().end()..];
+        match body.find(&closing) {
+            Some(end) => {
+                literals.push(body[..end].to_owned());
+                rest = &body[end + closing.len()..];
+            }
+            None => break,
+        }
+    }
+    literals
+        .chunks_exact(2)
+        .map(|pair| Sample {
+            input: pair[0].clone(),
+            output: pair[1].clone(),
+        })
+        .collect()
 }
 
 /// Generate a `tests` module as a String which check that the funciton passes all sample cases
+///
+/// Tests are numbered by their positions in `samples`, so samples which the caller filtered out
+/// leave no gaps in the names.
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::{
+///     generator::{self, CompareMode, TestFramework, TestOptions},
+///     Sample,
+/// };
+///
+/// let options = TestOptions {
+///     framework: TestFramework::AssertCmd,
+///     compare_mode: CompareMode::Exact,
+///     float_tolerance: None,
+///     interactive: false,
+/// };
+/// let samples: Vec<_> = ["1\n", "", "3\n"]
+///     .iter()
+///     .map(|input| Sample {
+///         input: input.to_string(),
+///         output: input.to_string(),
+///     })
+///     .filter(|sample| !sample.input.is_empty())
+///     .collect();
+/// let tests = generator::generate_test_cases(&options, "abc001", "a", &samples);
+/// assert!(tests.contains("fn sample_1()"));
+/// assert!(tests.contains("fn sample_2()"));
+/// assert!(!tests.contains("fn sample_3()"));
+/// ```
 pub fn generate_test_cases(
+    options: &TestOptions,
     project_name: &str,
     module_name: &str,
-    samples: &[(String, String)],
+    samples: &[Sample],
 ) -> String {
     let samples: String = samples
         .iter()
         .enumerate()
-        .map(|(index, (input, output))| {
+        .map(|(index, Sample { input, output })| {
             generate_sample(
+                options,
                 project_name,
                 module_name,
                 &format!("sample_{}", index + 1),
@@ -105,14 +1226,373 @@ pub fn generate_test_cases(
             )
         })
         .collect();
+    // Stubs of interactive tasks use nothing, so not even a blank line is left for the uses
+    let uses = match options.framework {
+        _ if options.interactive => "",
+        TestFramework::AssertCmd => {
+            "    use assert_cmd::Command;\n    use predicates::prelude::*;\n\n"
+        }
+        TestFramework::CliTestDir => "    use cli_test_dir::*;\n\n",
+    };
     format!(
         r#"#[cfg(test)]
 mod tests {{
-    use cli_test_dir::*;
-
-{samples}
+{uses}{samples}
 }}
 "#,
+        uses = uses,
         samples = samples
     )
 }
+
+/// `[dev-dependencies]` which the generated benchmarks need
+pub const BENCH_DEV_DEPENDENCIES: &str = "criterion = \"0.3\"";
+
+/// Generate `[[bench]]` entries as a String to be appended to Cargo.toml,
+/// which run `benches/<name>.rs` with `criterion` instead of the default harness
+pub fn generate_bench_entries(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("\n[[bench]]\nname = \"{}\"\nharness = false\n", name))
+        .collect()
+}
+
+/// Generate a `criterion` benchmark as a String which runs the binary named `task_name`
+/// with each sample input
+///
+/// # Examples
+/// ```
+/// use atcoder4rust::{generator, Sample};
+///
+/// let bench = generator::generate_bench_rs(
+///     "a",
+///     &[Sample {
+///         input: "1 2\n".to_owned(),
+///         output: "3\n".to_owned(),
+///     }],
+/// );
+/// assert!(bench.contains(r#"env!("CARGO_BIN_EXE_a")"#));
+/// assert!(bench.contains("r\"1 2\n\""));
+/// ```
+pub fn generate_bench_rs(task_name: &str, samples: &[Sample]) -> String {
+    generate_bench_rs_with_bin(task_name, task_name, samples)
+}
+
+/// Generate a `criterion` benchmark as a String which runs the binary named `bin_name`
+/// with the task's name as its argument and each sample input as its stdin
+pub fn generate_bench_rs_with_bin(bin_name: &str, task_name: &str, samples: &[Sample]) -> String {
+    let inputs: String = samples
+        .iter()
+        .map(|sample| format!("    {},\n", raw_string_literal(&sample.input)))
+        .collect();
+    format!(
+        r#"use std::{{
+    io::Write,
+    process::{{Command, Stdio}},
+}};
+
+use criterion::{{criterion_group, criterion_main, Criterion, Throughput}};
+
+const INPUTS: [&str; {count}] = [
+{inputs}];
+
+fn run(input: &str) {{
+    let mut child = Command::new(env!("CARGO_BIN_EXE_{bin_name}"))
+        .arg("{task_name}")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}}
+
+fn bench(c: &mut Criterion) {{
+    let mut group = c.benchmark_group("{task_name}");
+    for (index, input) in INPUTS.iter().enumerate() {{
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(format!("sample_{{}}", index + 1), |b| b.iter(|| run(input)));
+    }}
+    group.finish();
+}}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
+"#,
+        count = samples.len(),
+        inputs = inputs,
+        bin_name = bin_name,
+        task_name = task_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(compare_mode: CompareMode, float_tolerance: Option<f64>) -> TestOptions {
+        TestOptions {
+            framework: TestFramework::AssertCmd,
+            compare_mode,
+            float_tolerance,
+            interactive: false,
+        }
+    }
+
+    /// Generate main.rs, check that it parses, and return the names of its modules and arms
+    fn parse_main_rs(task_names: &[&str]) -> (Vec<String>, Vec<String>) {
+        let main_rs = generate_main_rs(task_names.iter().map(|name| name.to_string()).collect());
+        let file = match syn::parse_file(&main_rs) {
+            Ok(file) => file,
+            Err(error) => panic!("{}\n{}", error, main_rs),
+        };
+        let modules = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Mod(module) => Some(module.ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        let arms = main_rs
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('"'))
+            .filter_map(|line| line.split('"').nth(1))
+            .map(str::to_owned)
+            .collect();
+        (modules, arms)
+    }
+
+    #[test]
+    fn main_rs_with_a_task() {
+        assert_eq!(parse_main_rs(&["a"]), (vec!["a".into()], vec!["a".into()]));
+    }
+
+    #[test]
+    fn main_rs_sorts_tasks() {
+        let (modules, arms) = parse_main_rs(&["c", "b", "a"]);
+        assert_eq!(modules, ["a", "b", "c"]);
+        assert_eq!(arms, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn main_rs_in_natural_order() {
+        let (modules, arms) = parse_main_rs(&["ex", "b", "g", "a", "f"]);
+        assert_eq!(modules, ["a", "b", "f", "g", "ex"]);
+        assert_eq!(arms, ["a", "b", "f", "g", "ex"]);
+
+        let (modules, arms) = parse_main_rs(&["a10", "a1", "a2"]);
+        assert_eq!(modules, ["a1", "a2", "a10"]);
+        assert_eq!(arms, ["a1", "a2", "a10"]);
+    }
+
+    #[test]
+    fn main_rs_with_tasks_starting_with_digits() {
+        let (modules, arms) = parse_main_rs(&["2B", "1A"]);
+        assert_eq!(modules, ["_1a", "_2b"]);
+        assert_eq!(arms, ["1a", "2b"]);
+    }
+
+    #[test]
+    fn main_rs_with_keyword_tasks() {
+        let (modules, arms) = parse_main_rs(&["mod", "fn", "self"]);
+        assert_eq!(modules, ["r#fn", "r#mod", "_self"]);
+        assert_eq!(arms, ["fn", "mod", "self"]);
+    }
+
+    #[test]
+    fn main_rs_without_tasks() {
+        assert_eq!(parse_main_rs(&[]), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn github_actions_workflow_is_valid_yaml() {
+        let workflow = generate_github_actions_workflow("abc001", "stable");
+        let workflow: serde_yaml::Value = match serde_yaml::from_str(&workflow) {
+            Ok(value) => value,
+            Err(error) => panic!("{}\n{}", error, workflow),
+        };
+        assert_eq!(workflow["name"].as_str(), Some("Test abc001"));
+        let steps = workflow["jobs"]["test_abc001"]["steps"]
+            .as_sequence()
+            .unwrap();
+        assert_eq!(steps[1]["with"]["toolchain"].as_str(), Some("stable"));
+        assert_eq!(
+            steps[2]["with"]["path"].as_str(),
+            Some("~/.cargo\ntarget\n")
+        );
+        assert_eq!(steps[3]["run"].as_str(), Some("cargo test"));
+    }
+
+    #[test]
+    fn workspace_dependencies_in_tables_and_multiple_lines() {
+        let dependencies = r#"# comment
+proconio = { version = "0.3.6", features = [
+    "derive",
+] }
+num = "0.2.1"
+
+[dependencies.itertools]
+version = "0.9.0"
+"#;
+        let members = generate_workspace_member_dependencies(dependencies).unwrap();
+        assert_eq!(
+            members,
+            "proconio = { workspace = true }\nnum = { workspace = true }\nitertools = { workspace = true }"
+        );
+
+        let root: toml::Value = toml::from_str(&generate_workspace_toml(
+            &["tasks/a"],
+            dependencies,
+            "2021",
+            false,
+        ))
+        .unwrap();
+        assert!(root.get("dependencies").is_none(), "{}", root);
+        let shared = root["workspace"]["dependencies"].as_table().unwrap();
+        let names: Vec<_> = shared.keys().map(String::as_str).collect();
+        assert_eq!(names, ["itertools", "num", "proconio"]);
+        assert_eq!(shared["itertools"]["version"].as_str(), Some("0.9.0"));
+    }
+
+    #[test]
+    fn resolver_is_only_in_the_root_of_a_workspace() {
+        let root = generate_workspace_toml(&["tasks/a"], "", "2021", false);
+        assert!(root.contains("[workspace]\nresolver = \"2\"\n"), "{}", root);
+        let member = generate_workspace_member_cargo_toml("a", None, "", "", "2021");
+        assert!(!member.contains("resolver"), "{}", member);
+        let member: toml::Value = toml::from_str(&member).unwrap();
+        assert_eq!(member["package"]["edition"].as_str(), Some("2021"));
+        assert_eq!(member["package"]["name"].as_str(), Some("a"));
+    }
+
+    #[test]
+    fn invalid_dependencies_are_an_error() {
+        assert!(generate_workspace_member_dependencies("proconio = ").is_err());
+    }
+
+    #[test]
+    fn interactive_tests_have_no_blank_uses_line() {
+        let options = TestOptions {
+            interactive: true,
+            ..options(CompareMode::Exact, None)
+        };
+        let sample = Sample {
+            input: "1\n".to_owned(),
+            output: "1\n".to_owned(),
+        };
+        let tests = generate_test_cases(&options, "abc001", "a", &[sample]);
+        assert!(
+            tests.starts_with("#[cfg(test)]\nmod tests {\n    #[test]\n"),
+            "{}",
+            tests
+        );
+        assert!(
+            tests
+                .lines()
+                .all(|line| line.is_empty() || !line.trim().is_empty()),
+            "{}",
+            tests
+        );
+        if let Err(error) = syn::parse_file(&tests) {
+            panic!("{}\n{}", error, tests);
+        }
+    }
+
+    #[test]
+    fn raw_string_delimiters_outnumber_the_hashes_after_quotes() {
+        assert_eq!(min_raw_delimiters("1 2\n"), 1);
+        assert_eq!(min_raw_delimiters("\"quoted\""), 1);
+        assert_eq!(min_raw_delimiters(r##"r#"1"#"##), 2);
+        assert_eq!(min_raw_delimiters("\"## \"#"), 3);
+        assert_eq!(min_raw_delimiters("\"########"), 9);
+        for text in &["1 2\n", r##"r#"1"#"##, "\"## \"#", "#\"#", "\"########"] {
+            let literal = syn::parse_str::<syn::LitStr>(&raw_string_literal(text)).unwrap();
+            assert_eq!(literal.value(), *text);
+        }
+    }
+
+    #[test]
+    fn samples_with_raw_string_delimiters_generate_valid_rust() {
+        let samples = [
+            Sample {
+                input: r##"r#"1"#"##.to_owned(),
+                output: "\"##\n".to_owned(),
+            },
+            Sample {
+                input: "\"".to_owned(),
+                output: "#\"".to_owned(),
+            },
+        ];
+        for framework in &[TestFramework::AssertCmd, TestFramework::CliTestDir] {
+            for (compare_mode, float_tolerance) in &[
+                (CompareMode::Exact, None),
+                (CompareMode::Trim, None),
+                (CompareMode::Exact, Some(1e-6)),
+            ] {
+                let options = TestOptions {
+                    framework: *framework,
+                    ..options(*compare_mode, *float_tolerance)
+                };
+                let tests = generate_test_cases(&options, "abc001", "a", &samples);
+                if let Err(error) = syn::parse_file(&tests) {
+                    panic!("{}\n{}", error, tests);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_normalizer_is_copied_from_compare_rs() {
+        for name in &CompareMode::NAMES {
+            let mode = CompareMode::from_name(name).unwrap();
+            let test = generate_sample(&options(mode, None), "abc001", "a", "sample_1", "", "");
+            match mode.normalizer() {
+                Some(normalizer) => {
+                    assert!(test.contains(&format!(
+                        "        fn {}(text: &str) -> String {{\n",
+                        normalizer
+                    )));
+                    assert!(test.contains(&format!("assert_eq!({}(&stdout),", normalizer)));
+                }
+                None => assert!(test.contains("predicate::str::diff(")),
+            }
+            assert!(!test.contains("pub fn"));
+        }
+    }
+
+    #[test]
+    fn float_tolerance_copies_assert_close() {
+        let test = generate_sample(
+            &options(CompareMode::Exact, Some(1e-6)),
+            "abc001",
+            "a",
+            "sample_1",
+            "",
+            "0.5\n",
+        );
+        assert!(test
+            .contains("        fn assert_close(actual: &str, expected: &str, tolerance: f64) {\n"));
+        assert!(test.contains("assert_close(&stdout, r#\"0.5\n\"#, 1e-6);"));
+    }
+
+    #[test]
+    fn float_tolerance_takes_precedence_over_the_compare_mode() {
+        let test = generate_sample(
+            &options(CompareMode::Multiset, Some(1e-6)),
+            "abc001",
+            "a",
+            "sample_1",
+            "",
+            "",
+        );
+        assert!(test.contains("assert_close(&stdout,"));
+        assert!(!test.contains("fn multiset("));
+    }
+}
@@ -0,0 +1,167 @@
+//! Comparisons which generated tests make between the output and the expected one
+//!
+//! Nothing in this crate calls these functions: `CompareMode` and `--float-tolerance` copy their
+//! source into generated tests, and they are written here so that they are compiled and tested.
+//! Only the function for the mode is copied, so they must not call each other.
+
+/// `CompareMode::CaseInsensitive`
+pub fn case_insensitive(text: &str) -> String {
+    text.to_lowercase()
+}
+
+/// `CompareMode::Trim`, which also ignores the difference between LF and CRLF
+pub fn trim(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n")
+}
+
+/// `CompareMode::TrimCaseInsensitive`
+pub fn trim_case_insensitive(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase()
+}
+
+/// `CompareMode::Multiset`
+pub fn multiset(text: &str) -> String {
+    let mut tokens: Vec<_> = text.split_whitespace().collect();
+    tokens.sort_unstable();
+    tokens.join(" ")
+}
+
+/// `CompareMode::MultisetLines`
+pub fn multiset_lines(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let mut tokens: Vec<_> = line.split_whitespace().collect();
+            tokens.sort_unstable();
+            tokens.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `--float-tolerance`, which compares tokens as floats if both are floats, or as they are
+///
+/// NaN is only equal to NaN, and infinities are only equal to themselves.
+pub fn assert_close(actual: &str, expected: &str, tolerance: f64) {
+    let expected: Vec<_> = expected.split_whitespace().collect();
+    let actual: Vec<_> = actual.split_whitespace().collect();
+    assert_eq!(actual.len(), expected.len(), "the number of tokens differs");
+    for (actual, expected) in actual.iter().zip(&expected) {
+        match (actual.parse::<f64>(), expected.parse::<f64>()) {
+            (Ok(a), Ok(b)) if a.is_nan() || b.is_nan() => {
+                assert!(a.is_nan() && b.is_nan(), "{} != {}", actual, expected)
+            }
+            (Ok(a), Ok(b)) if a.is_infinite() || b.is_infinite() => {
+                assert_eq!(a, b, "{} != {}", actual, expected)
+            }
+            (Ok(a), Ok(b)) => assert!(
+                (a - b).abs() < tolerance,
+                "{} is not close to {}",
+                actual,
+                expected
+            ),
+            _ => assert_eq!(actual, expected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+
+    fn is_close(actual: &str, expected: &str) -> bool {
+        panic::catch_unwind(|| assert_close(actual, expected, 1e-6)).is_ok()
+    }
+
+    #[test]
+    fn floats_within_the_tolerance_are_close() {
+        assert!(is_close("3.1415926", "3.14159265"));
+        assert!(!is_close("3.14", "3.15"));
+        assert!(is_close("2 0.5000001 Yes\n", "2 0.5 Yes\n"));
+        assert!(!is_close("2 0.5 No\n", "2 0.5 Yes\n"));
+    }
+
+    #[test]
+    fn nan_is_only_close_to_nan() {
+        assert!(is_close("NaN", "NaN"));
+        assert!(!is_close("NaN", "0"));
+        assert!(!is_close("0", "NaN"));
+    }
+
+    #[test]
+    fn infinities_are_only_close_to_themselves() {
+        assert!(is_close("inf", "inf"));
+        assert!(is_close("-inf", "-inf"));
+        assert!(!is_close("inf", "-inf"));
+        assert!(!is_close("1e308", "inf"));
+    }
+
+    #[test]
+    fn exponent_notation_is_parsed() {
+        assert!(is_close("1e-3", "0.001"));
+        assert!(is_close("1.5E+2", "150"));
+        assert!(!is_close("1e-3", "0.01"));
+    }
+
+    #[test]
+    fn negative_zero_is_close_to_zero() {
+        assert!(is_close("-0", "0"));
+        assert!(is_close("-0.0000", "0.0"));
+    }
+
+    #[test]
+    fn missing_tokens_are_not_close() {
+        assert!(!is_close("1", "1 2"));
+        assert!(!is_close("1 2", "1"));
+        assert!(is_close("", "\n"));
+    }
+
+    #[test]
+    fn trim_ignores_whitespaces_around_each_line_and_line_endings() {
+        assert_eq!(trim("  3  \r\n4 \n"), trim("3\n4"));
+        assert_eq!(trim("3\r\n"), trim("3\n"));
+        assert_ne!(trim("1  2"), trim("1 2"));
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(case_insensitive("Yes"), case_insensitive("YES"));
+        assert_ne!(case_insensitive("Yes"), case_insensitive("Yes "));
+        assert_eq!(
+            trim_case_insensitive(" yes\r\n"),
+            trim_case_insensitive("YES\n")
+        );
+    }
+
+    #[test]
+    fn multiset_ignores_the_order_but_counts_duplicates() {
+        assert_eq!(multiset("3 1 2 1\n"), multiset("1 1 2 3\n"));
+        assert_eq!(multiset("1\n2 3"), multiset("3 2 1"));
+        assert_ne!(multiset("1 1 2"), multiset("1 2 2"));
+        assert_ne!(multiset("1 1 2"), multiset("1 2"));
+        assert_eq!(multiset("5\n"), multiset("5"));
+    }
+
+    #[test]
+    fn multiset_of_empty_output_is_empty() {
+        assert_eq!(multiset(""), "");
+        assert_eq!(multiset("\n"), "");
+        assert_ne!(multiset(""), multiset("0"));
+        assert_eq!(multiset_lines(""), multiset_lines("\n"));
+    }
+
+    #[test]
+    fn multiset_lines_ignores_the_order_in_each_line_only() {
+        assert_eq!(
+            multiset_lines("2 1\n3 4 3\n"),
+            multiset_lines("1 2\n3 3 4\n")
+        );
+        assert_ne!(multiset_lines("1\n2"), multiset_lines("2\n1"));
+        assert_ne!(multiset_lines("1 1\n2"), multiset_lines("1\n1 2"));
+    }
+}
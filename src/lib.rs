@@ -0,0 +1,14 @@
+//! Scraping primitives for AtCoder, on which the `atcoder4rust` command is built
+
+mod atcoder;
+pub mod error;
+pub mod generator;
+
+pub use atcoder::{
+    get_cookies, get_csrf_token, get_languages, get_samples, is_logged_in, is_login_page, login,
+    parse_contest_samples, parse_languages, parse_latest_status, parse_problem_meta,
+    parse_problem_statement, parse_problem_title, parse_samples, parse_statement_html,
+    parse_task_list, send_with_retry, submit, validate_session, wait_for_verdict, ContestType,
+    Language, ProblemMeta, RetryPolicy, Sample, Task, TaskLink, RUST_LANGUAGE_ID,
+};
+pub use error::Error;
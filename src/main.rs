@@ -1,209 +1,2063 @@
 use std::{
-    collections::HashMap,
     env,
-    fs::{self, File, OpenOptions},
-    io::{self, BufRead, BufReader, BufWriter, Read, Write},
-    path::Path,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{self, Child, Command},
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
 };
 
-use clap::{app_from_crate, crate_authors, crate_description, crate_name, crate_version, Arg};
-use futures::future::join_all;
-use percent_encoding;
-use reqwest::{
-    header::{self, HeaderMap, HeaderValue},
-    Client, Response, StatusCode, Url,
+use atcoder4rust::{
+    generator::{self, CompareMode, TestFramework, TestOptions},
+    get_languages, get_samples, is_login_page, login, parse_problem_statement, parse_task_list,
+    send_with_retry, validate_session, wait_for_verdict, Error, RetryPolicy, Sample, Task,
+    TaskLink, RUST_LANGUAGE_ID,
 };
-use scraper::{Html, Selector};
-use tokio::stream::{self, StreamExt};
+use chrono::{DateTime, Local, Utc};
+use clap::{
+    app_from_crate, crate_authors, crate_description, crate_name, crate_version, value_t_or_exit,
+    App, AppSettings, Arg, ArgMatches, Shell, SubCommand,
+};
+use comfy_table::Table;
+use difference::{Changeset, Difference};
+use indexmap::IndexMap;
+use indicatif::MultiProgress;
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use reqwest::{header::HeaderMap, Client, StatusCode, Url};
+use toml_edit::{Array, Document};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::EnvFilter;
 
-mod error;
-mod generator;
-use error::Error;
+mod cache;
+mod config;
+mod cookie;
+mod metadata;
+mod writer;
+use cache::SampleCache;
+use config::Config;
+use cookie::{CookieFormat, CookieStore, FileCookieStore, KeychainCookieStore};
+use metadata::{ContestMetadata, TestSettings};
+use writer::{DiskWriter, DryRunWriter, FileWriter, JsonWriter, SkipExistingWriter};
 
-fn get_csrf_token(response: &Response) -> Result<String, Error> {
-    response
-        .headers()
-        .get_all("set-cookie")
-        .iter()
-        .filter_map(|value| value.to_str().ok())
-        .filter(|value| value.starts_with("REVEL_SESSION"))
-        .flat_map(|value| {
-            value
-                .split("%00")
-                .filter(|value| value.starts_with("csrf_token"))
-        })
-        .map(percent_encoding::percent_decode_str)
-        .map(|decoded| decoded.decode_utf8_lossy())
-        .filter_map(|token| {
-            token
-                .split(":")
-                .nth(1)
-                .and_then(|token| Some(token.to_string()))
-        })
-        .next()
-        .ok_or(Error::Invalid("Could not find csrf_token".to_string()))
+/// Values of `--layout`, which the config file may also give
+const LAYOUTS: [&str; 2] = ["modules", "bin"];
+
+fn retry_policy(args: &ArgMatches, config: &Config) -> RetryPolicy {
+    RetryPolicy {
+        retries: value_or_config(args, "retries", config.retries),
+        delay: Duration::from_secs(value_or_config(args, "retry-delay", config.retry_delay)),
+        max_wait: Duration::from_secs(value_or_config(
+            args,
+            "max-retry-wait",
+            config.max_retry_wait,
+        )),
+    }
 }
 
-fn get_cookies(response: &Response) -> HeaderMap {
-    response
-        .cookies()
-        .map(|cookie| {
-            (
-                header::COOKIE,
-                format!("{}={}", cookie.name(), cookie.value())
-                    .parse()
-                    .unwrap(),
-            )
+/// Value of the option, where the config file overrides only its default value
+fn value_or_config<T: FromStr>(args: &ArgMatches, name: &str, config: Option<T>) -> T {
+    match config {
+        Some(value) if args.occurrences_of(name) == 0 => value,
+        _ => value_t_or_exit!(args, name, T),
+    }
+}
+
+/// Directory in which contest projects are
+fn project_root(args: &ArgMatches, config: &Config) -> Result<PathBuf, Error> {
+    if let Some(root_path) = args.value_of("root").or(config.root.as_deref()) {
+        Ok(Path::new(root_path).to_owned())
+    } else {
+        Ok(env::current_dir()?)
+    }
+}
+
+/// Lowercase the contest id, since URLs of AtCoder are case-sensitive
+fn normalize_contest_id(contest_id: &str) -> String {
+    let normalized = contest_id.to_lowercase();
+    if normalized != contest_id {
+        warn!("Contest ID normalized: {} → {}", contest_id, normalized);
+    }
+    normalized
+}
+
+/// Contest id and root URL of the argument, which is either a contest id or a URL in the contest
+/// (e.g. `https://atcoder.jp/contests/abc001/tasks`) copied from the browser
+fn parse_contest_arg(arg: &str, root_url: &Url) -> Result<(String, Url), Error> {
+    if !arg.starts_with("http://") && !arg.starts_with("https://") {
+        return Ok((normalize_contest_id(arg), root_url.clone()));
+    }
+    let url = Url::parse(arg)?;
+    let contest_id = url
+        .path_segments()
+        .and_then(|mut segments| {
+            segments.find(|segment| *segment == "contests")?;
+            segments.next()
         })
-        .collect()
-}
-
-fn parse_samples(text: &str) -> Result<Vec<(String, String)>, Error> {
-    let document = Html::parse_document(&text);
-    let (inputs, outputs): (Vec<_>, Vec<_>) = document
-        .select(&Selector::parse("#task-statement .part").unwrap())
-        .filter_map(|part| {
-            part.select(&Selector::parse("h3").unwrap())
-                .filter_map(|h3| {
-                    if let Some(text) = h3.text().find(|text| text.starts_with("入力例")) {
-                        text.split_whitespace()
-                            .nth(1)
-                            .and_then(|index| Some((part, index, true)))
-                    } else if let Some(text) = h3.text().find(|text| text.starts_with("出力例"))
-                    {
-                        text.split_whitespace()
-                            .nth(1)
-                            .and_then(|index| Some((part, index, false)))
-                    } else {
-                        None
-                    }
-                })
-                .next()
+        .filter(|contest_id| !contest_id.is_empty())
+        .ok_or_else(|| Error::Invalid(format!("No contest id in the URL: {}", url)))?;
+    Ok((normalize_contest_id(contest_id), url.join("/")?))
+}
+
+/// Value of the environment variable, where an empty value is regarded as absent
+fn non_empty_env(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+/// Matches which have the global arg `name`
+///
+/// clap puts a global arg given after the subcommand (e.g. `submit --proxy URL`) only in the
+/// matches of the subcommand, so they are searched before the top-level ones.
+fn global_matches<'a, 'b>(args: &'b ArgMatches<'a>, name: &str) -> &'b ArgMatches<'a> {
+    let mut found = args;
+    let mut matches = args;
+    while let (_, Some(subcommand)) = matches.subcommand() {
+        matches = subcommand;
+        if matches.occurrences_of(name) > 0 {
+            found = matches;
+        }
+    }
+    found
+}
+
+/// Proxy given by `--proxy`, or by `HTTPS_PROXY` / `HTTP_PROXY` unless `--no-proxy` is set
+fn proxy_url(args: &ArgMatches) -> Option<String> {
+    if global_matches(args, "no-proxy").is_present("no-proxy") {
+        return None;
+    }
+    global_matches(args, "proxy")
+        .value_of("proxy")
+        .map(str::to_owned)
+        .or_else(|| {
+            ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+                .iter()
+                .find_map(|key| non_empty_env(key))
         })
-        .filter_map(|(part, index, is_input)| {
-            part.select(&Selector::parse("pre").unwrap())
-                .map(|pre| (pre.inner_html(), index, is_input))
-                .next()
+}
+
+/// `--timeout` of the whole request
+fn timeout(args: &ArgMatches, config: &Config) -> Duration {
+    let matches = global_matches(args, "timeout");
+    Duration::from_secs(value_or_config(matches, "timeout", config.timeout))
+}
+
+/// `--connect-timeout`, which is only limited by `--timeout` if not given
+fn connect_timeout(args: &ArgMatches, config: &Config) -> Option<Duration> {
+    let matches = global_matches(args, "connect-timeout");
+    if matches.is_present("connect-timeout") {
+        Some(Duration::from_secs(value_t_or_exit!(
+            matches,
+            "connect-timeout",
+            u64
+        )))
+    } else {
+        config.connect_timeout.map(Duration::from_secs)
+    }
+}
+
+fn build_client(args: &ArgMatches, config: &Config) -> Result<Client, Error> {
+    let user_agent = match global_matches(args, "user-agent").value_of("user-agent") {
+        Some(user_agent) => {
+            debug!("Using User-Agent: {}", user_agent);
+            user_agent.to_owned()
+        }
+        None => format!("{}/{}", crate_name!(), env!("CARGO_PKG_VERSION")),
+    };
+    let mut builder = Client::builder()
+        .cookie_store(true)
+        .user_agent(user_agent)
+        .timeout(timeout(args, config));
+    if let Some(connect_timeout) = connect_timeout(args, config) {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    if let Some(proxy) = proxy_url(args) {
+        let url = Url::parse(&proxy).map_err(|error| {
+            error!("Invalid proxy URL `{}`: {}", proxy, error);
+            error
+        })?;
+        builder = builder.proxy(reqwest::Proxy::https(url)?);
+    } else if global_matches(args, "no-proxy").is_present("no-proxy") {
+        // reqwest also reads the proxy from the environment by itself
+        builder = builder.no_proxy();
+    }
+    Ok(builder.build()?)
+}
+
+/// Read the password from the first line of the file
+fn read_password_file(path: &str) -> Result<String, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buf = String::new();
+    reader.read_line(&mut buf)?;
+    Ok(buf.trim().to_owned())
+}
+
+/// Load the local cookie file, or login interactively & save cookies
+async fn authenticate(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+    retry: &RetryPolicy,
+) -> Result<HeaderMap, Error> {
+    if args.is_present("password") && args.is_present("password-file") {
+        return Err(Error::Invalid(
+            "Cannot use both --password and --password-file".to_owned(),
+        ));
+    }
+    let store: Box<dyn CookieStore> = if args.is_present("keychain") {
+        Box::new(KeychainCookieStore)
+    } else {
+        let cookie = args.value_of("cookie").or(config.cookie.as_deref());
+        // Find a local cookie file
+        let path = if let Some(path) = cookie {
+            Path::new(path).to_owned()
+        } else {
+            env::current_dir()?.join("cookie.txt")
+        };
+        Box::new(FileCookieStore {
+            path,
+            format: CookieFormat::from_name(args.value_of("cookie-format").unwrap()).unwrap(),
+            lock_timeout: Duration::from_secs(value_t_or_exit!(args, "lock-timeout", u64)),
         })
-        .partition(|(_, _, is_input)| *is_input);
-    Ok(inputs
+    };
+    if let Some(cookies) = store.load()? {
+        if !args.is_present("validate-session")
+            || validate_session(client, root_url, &cookies, retry).await?
+        {
+            return Ok(cookies);
+        }
+        warn!(
+            "The session in {} has expired; logging in again",
+            store.location()
+        );
+    }
+
+    // Credentials in the environment are for non-interactive uses such as CI
+    let env_username = non_empty_env("ATCODER_USER");
+    let env_password = non_empty_env("ATCODER_PASS");
+    let uses_env = (args.value_of("user").is_none() && env_username.is_some())
+        || (args.value_of("password").is_none()
+            && args.value_of("password-file").is_none()
+            && env_password.is_some());
+    if uses_env {
+        debug!("Using credentials from environment");
+    }
+    let username = if let Some(username) = args
+        .value_of("user")
+        .map(str::to_owned)
+        .or(env_username)
+        .or_else(|| config.user.clone())
+    {
+        username
+    } else {
+        print!("User name: ");
+        io::stdout().flush()?;
+        let mut buf = String::new();
+        io::stdin().read_line(&mut buf)?;
+        buf.trim().to_owned()
+    };
+    let password_file = args
+        .value_of("password-file")
+        .map(read_password_file)
+        .transpose()?;
+    let password = if let Some(password) = args
+        .value_of("password")
+        .map(str::to_owned)
+        .or(password_file)
+        .or(env_password)
+    {
+        password
+    } else {
+        print!("Password: ");
+        io::stdout().flush()?;
+        let mut buf = String::new();
+        io::stdin().read_line(&mut buf)?;
+        buf.trim().to_owned()
+    };
+    // login() fails with Error::Auth if AtCoder redirects back to the login page
+    let cookies = login(root_url.join("login")?, client, &username, &password, retry).await?;
+    store.save(&cookies)?;
+    Ok(cookies)
+}
+
+/// Path to the source file of the task in the generated project
+fn task_source_path(project_path: &Path, task: &str) -> PathBuf {
+    let task = generator::sanitize_module_name(task);
+    let member_path = project_path
+        .join("tasks")
+        .join(&task)
+        .join("src")
+        .join("main.rs");
+    let bin_path = project_path
+        .join("src")
+        .join("bin")
+        .join(format!("{}.rs", task));
+    if member_path.exists() {
+        member_path
+    } else if bin_path.exists() {
+        bin_path
+    } else {
+        project_path.join("src").join(format!("{}.rs", task))
+    }
+}
+
+/// Language ID given by `--lang`, which must be numeric like the ones in the submit form
+fn language_id(args: &ArgMatches) -> Result<Option<String>, Error> {
+    match args.value_of("lang") {
+        Some(id) if id.parse::<u32>().is_err() => Err(Error::Invalid(format!(
+            "Language ID must be numeric (see `atcoder4rust list-languages`): {}",
+            id
+        ))),
+        id => Ok(id.map(str::to_owned)),
+    }
+}
+
+async fn run_submit(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let contest_id = &normalize_contest_id(args.value_of("contest id").unwrap());
+    let task = args.value_of("task").unwrap().to_lowercase();
+    let project_path = project_root(args, config)?.join(contest_id);
+    let metadata = ContestMetadata::find(&project_path)
+        .ok()
+        .map(|(_, metadata)| metadata);
+    // The task may be given with or without --task-prefix of the project
+    let (task, file_name) = match &metadata {
+        Some(metadata) => (
+            metadata.unprefixed(&task).to_owned(),
+            metadata.prefixed(&task),
+        ),
+        None => (task.clone(), task),
+    };
+    let source_path = if let Some(file) = args.value_of("file") {
+        Path::new(file).to_owned()
+    } else {
+        task_source_path(&project_path, &file_name)
+    };
+    // `--lang` overrides the language stored when the project was generated
+    let language_id = match language_id(args)? {
+        Some(id) => id,
+        None => metadata
+            .and_then(|metadata| metadata.language_id)
+            .unwrap_or_else(|| RUST_LANGUAGE_ID.to_owned()),
+    };
+    let mut reader = BufReader::new(File::open(&source_path)?);
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let retry = retry_policy(args, config);
+    let cookies = authenticate(args, config, client, root_url, &retry).await?;
+    let url = atcoder4rust::submit(
+        client,
+        root_url,
+        contest_id,
+        &task,
+        &source,
+        &language_id,
+        &cookies,
+        &retry,
+    )
+    .await?;
+    println!("Submitted {}", source_path.display());
+    let timeout = Duration::from_secs(value_t_or_exit!(args, "verdict-timeout", u64));
+    let verdict = wait_for_verdict(client, &url, &cookies, &retry, timeout).await?;
+    println!("{}", verdict);
+    Ok(())
+}
+
+/// Open the page of the task, or the tasks page of the contest, in the default browser
+fn run_open(args: &ArgMatches) -> Result<(), Error> {
+    let metadata = ContestMetadata::find(&env::current_dir()?)
+        .ok()
+        .map(|(_, metadata)| metadata);
+    let first = args.value_of("contest id");
+    let second = args.value_of("task");
+    // In a generated project, the contest id can be omitted
+    let (contest_id, task) = match (first, second, &metadata) {
+        (Some(contest_id), Some(task), _) => (normalize_contest_id(contest_id), Some(task)),
+        (Some(task), None, Some(metadata))
+            if metadata
+                .tasks
+                .contains(&metadata.prefixed(&task.to_lowercase())) =>
+        {
+            (metadata.contest_id.clone(), Some(task))
+        }
+        (Some(contest_id), None, _) => (normalize_contest_id(contest_id), None),
+        (None, _, Some(metadata)) => (metadata.contest_id.clone(), None),
+        (None, _, None) => {
+            return Err(Error::Invalid(format!(
+                "The contest id is required outside of a project ({} is not found)",
+                metadata::FILE_NAME
+            )))
+        }
+    };
+    let contest_url = Url::parse("https://atcoder.jp/")?
+        .join("contests/")?
+        .join(&format!("{}/", contest_id))?
+        .join("tasks")?;
+    let url = match task {
+        Some(task) => contest_url.join(&format!(
+            "tasks/{}_{}",
+            contest_id.replace('-', "_"),
+            metadata
+                .as_ref()
+                .map_or(task, |metadata| metadata.unprefixed(task))
+                .to_lowercase()
+        ))?,
+        None => contest_url,
+    };
+    println!("Opening {}", url);
+    open::that(url.as_str())?;
+    Ok(())
+}
+
+/// `cargo test` of the task, or of all tasks, in the generated project
+fn test_command(project_path: &Path, task: Option<&str>, release: bool) -> Command {
+    let mut command = Command::new("cargo");
+    command.arg("test").current_dir(project_path);
+    if release {
+        command.arg("--release");
+    }
+    if let Some(task) = task {
+        let task = generator::sanitize_module_name(task);
+        if project_path.join("tasks").join(&task).exists() {
+            command.args(&["--package", &task]);
+        } else {
+            command.args(&["--test", &task]);
+        }
+    }
+    command
+}
+
+fn run_test(args: &ArgMatches) -> Result<(), Error> {
+    let (project_path, metadata) = ContestMetadata::find(&env::current_dir()?)?;
+    // Files of the task are named after the prefixed name, e.g. tests/abc300_a.rs for `a`
+    let task = args
+        .value_of("task")
+        .map(|task| metadata.prefixed(&task.to_lowercase()));
+    // stdout and stderr are inherited, so that they are streamed as is
+    let status =
+        test_command(&project_path, task.as_deref(), args.is_present("release")).status()?;
+    process::exit(status.code().unwrap_or(1));
+}
+
+/// Run `cargo check` of the task, or of the whole project, in the current directory
+fn run_check(args: &ArgMatches) -> Result<(), Error> {
+    let (project_path, metadata) = ContestMetadata::find(&env::current_dir()?)?;
+    let mut command = Command::new("cargo");
+    command.arg("check").current_dir(&project_path);
+    if let Some(task) = args.value_of("task") {
+        let task = generator::sanitize_module_name(&metadata.prefixed(&task.to_lowercase()));
+        let bin_path = project_path
+            .join("src")
+            .join("bin")
+            .join(format!("{}.rs", task));
+        if project_path.join("tasks").join(&task).exists() {
+            command.args(&["--package", &task]);
+        } else if bin_path.exists() {
+            command.args(&["--bin", &task]);
+        } else {
+            warn!(
+                "{} is a module of main.rs, so the whole crate is checked",
+                task
+            );
+        }
+    }
+    let status = command.status()?;
+    process::exit(status.code().unwrap_or(1));
+}
+
+/// Task whose source is the changed file (e.g. `src/a.rs`, `src/bin/a.rs` or `tasks/a/src/main.rs`)
+fn changed_task(project_path: &Path, path: &Path, metadata: &ContestMetadata) -> Option<String> {
+    let relative = path.strip_prefix(project_path).ok()?;
+    let task = match relative.components().next()?.as_os_str().to_str()? {
+        "tasks" => relative.components().nth(1)?.as_os_str().to_str()?,
+        _ => relative.file_stem()?.to_str()?,
+    };
+    // e.g. main.rs of the dispatcher is not a task
+    metadata
+        .tasks
         .iter()
-        .map(|(input, _, _)| input.clone())
-        .zip(outputs.iter().map(|(output, _, _)| output.clone()))
-        .collect())
+        .find(|name| generator::sanitize_module_name(name) == task)
+        .cloned()
+}
+
+/// Run the tests of the task whenever its source is saved, until Ctrl+C is pressed
+fn run_watch(args: &ArgMatches) -> Result<(), Error> {
+    let (project_path, metadata) = ContestMetadata::find(&env::current_dir()?)?;
+    // Paths of events are canonicalized
+    let project_path = project_path.canonicalize()?;
+    let only = args
+        .value_of("task")
+        .map(|task| metadata.prefixed(&task.to_lowercase()));
+    let running: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
+    let handler_running = running.clone();
+    ctrlc::set_handler(move || {
+        if let Some(child) = handler_running.lock().unwrap().as_mut() {
+            let _ = child.kill();
+        }
+        process::exit(130);
+    })
+    .map_err(|error| Error::Invalid(format!("Could not handle Ctrl+C: {}", error)))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::watcher(sender, Duration::from_millis(500))
+        .map_err(|error| Error::Invalid(format!("Could not watch files: {}", error)))?;
+    let watched = if project_path.join("tasks").exists() {
+        project_path.join("tasks")
+    } else {
+        project_path.join("src")
+    };
+    watcher
+        .watch(&watched, RecursiveMode::Recursive)
+        .map_err(|error| Error::Invalid(format!("Could not watch files: {}", error)))?;
+    println!("Watching {} (press Ctrl+C to stop)", watched.display());
+    for event in receiver {
+        let path = match event {
+            DebouncedEvent::Write(path) | DebouncedEvent::Create(path) => path,
+            _ => continue,
+        };
+        if path.extension().and_then(|extension| extension.to_str()) != Some("rs") {
+            continue;
+        }
+        let task = match changed_task(&project_path, &path, &metadata) {
+            Some(task) => task,
+            None => continue,
+        };
+        if only.as_ref().map_or(false, |only| *only != task) {
+            continue;
+        }
+        println!("{}", "-".repeat(80));
+        println!("[{}] {}", Local::now().format("%H:%M:%S"), task);
+        let child = test_command(&project_path, Some(&task), args.is_present("release")).spawn()?;
+        *running.lock().unwrap() = Some(child);
+        // Poll the child so that the lock is released for the Ctrl+C handler
+        while running
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map_or(Ok(None), Child::try_wait)?
+            .is_none()
+        {
+            thread::sleep(Duration::from_millis(100));
+        }
+        *running.lock().unwrap() = None;
+    }
+    Ok(())
+}
+
+fn is_no_login(args: &ArgMatches, config: &Config) -> bool {
+    args.is_present("no-login") || config.no_login.unwrap_or(false)
 }
 
-async fn get_samples(
-    text: &str,
+/// Fetch the tasks page of the contest
+async fn fetch_tasks_page(
     client: &Client,
     root_url: &Url,
+    contest_id: &str,
     cookies: &Option<HeaderMap>,
-) -> Result<HashMap<String, Vec<(String, String)>>, Error> {
-    let document = Html::parse_document(text);
-    let selector = Selector::parse("tbody > tr").unwrap();
-    let samples = document
-        .select(&selector)
-        .filter_map(|tr| tr.select(&Selector::parse("td a").unwrap()).next())
-        .map(|a| {
-            let task_name = a.inner_html();
-            let url = a.value().attr("href").unwrap();
-            let root_url = root_url.clone();
-            let client = client.clone();
-            let cookies = cookies.clone();
-            async move {
-                let response = client
-                    .get(root_url.join(url)?)
-                    .headers(cookies.unwrap_or_default())
-                    .send()
-                    .await?;
-                let text = response.text().await?;
-                parse_samples(&text).and_then(|samples| Ok((task_name, samples)))
+    retry: &RetryPolicy,
+) -> Result<String, Error> {
+    let contest_url = root_url
+        .join("contests/")?
+        .join(&format!("{}/", contest_id))?
+        .join("tasks")?;
+    let response = send_with_retry(
+        || {
+            client
+                .get(contest_url.clone())
+                .headers(cookies.clone().unwrap_or_default())
+        },
+        retry,
+    )
+    .await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    // Private contests redirect to the login page, or show the login form as is
+    let redirected_to_login = response.url().path().ends_with("/login");
+    let text = response.text().await?;
+    if redirected_to_login || is_login_page(&text) {
+        return Err(Error::Auth("Contest requires login".to_owned()));
+    }
+    Ok(text)
+}
+
+async fn run_list(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let contest_id = &normalize_contest_id(args.value_of("contest id").unwrap());
+    let retry = retry_policy(args, config);
+    let cookies = if is_no_login(args, config) {
+        None
+    } else {
+        Some(authenticate(args, config, client, root_url, &retry).await?)
+    };
+    let html = fetch_tasks_page(client, root_url, contest_id, &cookies, &retry).await?;
+    let tasks = parse_task_list(&html)?
+        .into_iter()
+        .map(|task| {
+            Ok(TaskLink {
+                url: root_url.join(&task.url)?.to_string(),
+                ..task
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    if args.is_present("json") {
+        println!("{}", serde_json::to_string(&tasks)?);
+    } else {
+        for task in tasks {
+            println!("{}\t{}", task.name, task.url);
+        }
+    }
+    Ok(())
+}
+
+/// Options of the generated tests, where the config file gives the default framework
+fn test_options(args: &ArgMatches, config: &Config) -> Result<TestOptions, Error> {
+    let framework = match config.test_framework.as_deref() {
+        Some(name) if args.occurrences_of("test-framework") == 0 => TestFramework::from_name(name)
+            .ok_or_else(|| {
+                Error::Invalid(format!("Unknown test framework in the config: {}", name))
+            })?,
+        _ => args
+            .value_of("test-framework")
+            .and_then(TestFramework::from_name)
+            .unwrap_or_default(),
+    };
+    let compare_mode = match config.compare_mode.as_deref() {
+        Some(name) if args.occurrences_of("compare-mode") == 0 => CompareMode::from_name(name)
+            .ok_or_else(|| {
+                Error::Invalid(format!("Unknown compare mode in the config: {}", name))
+            })?,
+        _ => args
+            .value_of("compare-mode")
+            .and_then(CompareMode::from_name)
+            .unwrap_or_default(),
+    };
+    Ok(TestOptions {
+        framework,
+        compare_mode,
+        float_tolerance: if args.is_present("float-tolerance") {
+            Some(value_t_or_exit!(args, "float-tolerance", f64))
+        } else {
+            config.float_tolerance
+        },
+        interactive: args.is_present("interactive"),
+    })
+}
+
+/// Options of the tests which `update` regenerates, keeping the ones the project was generated with
+fn update_test_options(
+    args: &ArgMatches,
+    config: &Config,
+    metadata: &ContestMetadata,
+) -> Result<TestOptions, Error> {
+    let mut options = test_options(args, config)?;
+    let settings = match &metadata.tests {
+        Some(settings) => settings,
+        None => return Ok(options),
+    };
+    if args.occurrences_of("test-framework") == 0 {
+        options.framework = TestFramework::from_name(&settings.framework).ok_or_else(|| {
+            Error::Invalid(format!(
+                "Unknown test framework in {}: {}",
+                metadata::FILE_NAME,
+                settings.framework
+            ))
+        })?;
+    }
+    if args.occurrences_of("compare-mode") == 0 {
+        options.compare_mode = CompareMode::from_name(&settings.compare_mode).ok_or_else(|| {
+            Error::Invalid(format!(
+                "Unknown compare mode in {}: {}",
+                metadata::FILE_NAME,
+                settings.compare_mode
+            ))
+        })?;
+    }
+    if !args.is_present("float-tolerance") {
+        options.float_tolerance = settings.float_tolerance;
+    }
+    Ok(options)
+}
+
+/// Replace the last `#[cfg(test)]` block of the source with `tests`
+fn replace_test_block(source: &str, tests: &str) -> String {
+    match source.rfind("#[cfg(test)]") {
+        Some(index) => source[..index].to_owned() + tests,
+        None if source.is_empty() || source.ends_with('\n') => source.to_owned() + tests,
+        None => format!("{}\n{}", source, tests),
+    }
+}
+
+/// Path to the tests of the task in the generated project
+fn task_test_path(project_path: &Path, module_name: &str) -> PathBuf {
+    let member_path = project_path.join("tasks").join(module_name);
+    if member_path.exists() {
+        member_path.join("tests").join("sample.rs")
+    } else {
+        project_path
+            .join("tests")
+            .join(module_name.to_owned() + ".rs")
+    }
+}
+
+/// Fetch samples again and replace the tests of the generated project in the current directory
+async fn run_update(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let (project_path, metadata) = ContestMetadata::find(&env::current_dir()?)?;
+    let retry = retry_policy(args, config);
+    let cookies = if is_no_login(args, config) {
+        None
+    } else {
+        Some(authenticate(args, config, client, root_url, &retry).await?)
+    };
+    let html = fetch_tasks_page(client, root_url, &metadata.contest_id, &cookies, &retry).await?;
+    let tasks = get_samples(
+        &html,
+        client,
+        root_url,
+        &cookies,
+        &retry,
+        value_or_config(args, "parallel", config.parallel),
+        None,
+    )
+    .await?;
+    let test_options = update_test_options(args, config, &metadata)?;
+    let mut updated = 0;
+    for (key, task) in tasks {
+        let key = metadata.prefixed(&key);
+        let module_name = generator::sanitize_module_name(&key);
+        let path = task_test_path(&project_path, &module_name);
+        // Workspace members and binaries are named after the task, while modules share the contest's binary
+        let tests = if project_path.join("tasks").join(&module_name).exists()
+            || project_path.join("src").join("bin").exists()
+        {
+            generator::generate_test_cases(
+                &task_test_options(&test_options, &task),
+                &module_name,
+                &module_name,
+                &task.samples,
+            )
+        } else {
+            generator::generate_test_cases(
+                &task_test_options(&test_options, &task),
+                &metadata.contest_id,
+                &key.to_lowercase(),
+                &task.samples,
+            )
+        };
+        if !path.exists() {
+            warn!("{} does not exist", path.display());
+            continue;
+        }
+        let source = fs::read_to_string(&path)?;
+        let replaced = replace_test_block(&source, &tests);
+        if replaced != source {
+            fs::write(&path, replaced)?;
+            println!("Updated {}", path.display());
+            updated += 1;
+        }
+    }
+    if updated == 0 {
+        println!("All samples are up to date");
+    }
+    Ok(())
+}
+
+/// Print the line diff from `local` to `fetched` with the unchanged lines as context
+fn print_diff(label: &str, local: &str, fetched: &str) {
+    println!("--- {} (local)", label);
+    println!("+++ {} (AtCoder)", label);
+    for diff in Changeset::new(local, fetched, "\n").diffs {
+        let (prefix, text) = match diff {
+            Difference::Same(text) => (' ', text),
+            Difference::Add(text) => ('+', text),
+            Difference::Rem(text) => ('-', text),
+        };
+        for line in text.lines() {
+            println!("{}{}", prefix, line);
+        }
+    }
+}
+
+/// Fetch samples again and compare them with the ones embedded in the tests of the generated project
+async fn run_validate(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let (project_path, metadata) = ContestMetadata::find(&env::current_dir()?)?;
+    let retry = retry_policy(args, config);
+    let cookies = if is_no_login(args, config) {
+        None
+    } else {
+        Some(authenticate(args, config, client, root_url, &retry).await?)
+    };
+    let html = fetch_tasks_page(client, root_url, &metadata.contest_id, &cookies, &retry).await?;
+    let tasks = get_samples(
+        &html,
+        client,
+        root_url,
+        &cookies,
+        &retry,
+        value_or_config(args, "parallel", config.parallel),
+        None,
+    )
+    .await?;
+    let mut outdated = 0;
+    for (key, task) in tasks {
+        let key = metadata.prefixed(&key);
+        let path = task_test_path(&project_path, &generator::sanitize_module_name(&key));
+        if !path.exists() {
+            warn!("{} does not exist", path.display());
+            continue;
+        }
+        let local = generator::extract_samples(&fs::read_to_string(&path)?);
+        if local == task.samples {
+            continue;
+        }
+        outdated += 1;
+        println!("{}:", path.display());
+        for index in 0..local.len().max(task.samples.len()) {
+            let name = format!("sample_{}", index + 1);
+            match (local.get(index), task.samples.get(index)) {
+                (Some(local), Some(fetched)) => {
+                    if local.input != fetched.input {
+                        print_diff(&format!("{} input", name), &local.input, &fetched.input);
+                    }
+                    if local.output != fetched.output {
+                        print_diff(&format!("{} output", name), &local.output, &fetched.output);
+                    }
+                }
+                (None, Some(_)) => println!("Added {}", name),
+                (Some(_), None) => println!("Removed {}", name),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+    if outdated == 0 {
+        println!("All samples are up to date");
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!(
+            "Samples of {} tasks differ, run `atcoder4rust update` to replace them",
+            outdated
+        )))
+    }
+}
+
+/// Print the Rust languages which the contest accepts, with their IDs for `--lang`
+async fn run_list_languages(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let contest_id = match args.value_of("contest id") {
+        Some(contest_id) => normalize_contest_id(contest_id),
+        None => ContestMetadata::find(&env::current_dir()?)
+            .map(|(_, metadata)| metadata.contest_id)
+            .map_err(|_| {
+                Error::Invalid(format!(
+                    "The contest id is required outside of a project ({} is not found)",
+                    metadata::FILE_NAME
+                ))
+            })?,
+    };
+    let retry = retry_policy(args, config);
+    // The submit page is only shown to users who are logged in
+    let cookies = authenticate(args, config, client, root_url, &retry).await?;
+    for language in get_languages(client, root_url, &contest_id, &cookies, &retry)
+        .await?
+        .into_iter()
+        .filter(|language| language.name.starts_with("Rust"))
+    {
+        println!("{}\t{}", language.id, language.name);
+    }
+    Ok(())
+}
+
+/// Print the statement of the task in the terminal
+async fn run_show(
+    args: &ArgMatches<'_>,
+    config: &Config,
+    client: &Client,
+    root_url: &Url,
+) -> Result<(), Error> {
+    let contest_id = &normalize_contest_id(args.value_of("contest id").unwrap());
+    let task = args.value_of("task").unwrap();
+    let retry = retry_policy(args, config);
+    let cookies = if is_no_login(args, config) {
+        None
+    } else {
+        Some(authenticate(args, config, client, root_url, &retry).await?)
+    };
+    let html = fetch_tasks_page(client, root_url, contest_id, &cookies, &retry).await?;
+    let link = parse_task_list(&html)?
+        .into_iter()
+        .find(|link| link.name.eq_ignore_ascii_case(task))
+        .ok_or_else(|| Error::Invalid(format!("Task {} is not found in {}", task, contest_id)))?;
+    let url = root_url.join(&link.url)?;
+    let response = send_with_retry(
+        || {
+            client
+                .get(url.clone())
+                .headers(cookies.clone().unwrap_or_default())
+        },
+        &retry,
+    )
+    .await?;
+    if response.status() != StatusCode::OK {
+        return Err(Error::Http(response.status()));
+    }
+    let statement = parse_problem_statement(&response.text().await?);
+    if args.is_present("no-ansi") {
+        print!("{}", statement);
+    } else {
+        termimad::print_text(&statement);
+    }
+    Ok(())
+}
+
+/// Ask the user whether to continue
+fn confirm(message: &str) -> Result<bool, Error> {
+    print!("{} [y/N] ", message);
+    io::stdout().flush()?;
+    let mut buf = String::new();
+    io::stdin().read_line(&mut buf)?;
+    let answer = buf.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Source of the task file, which has comments of the task's title and limits above the template
+fn source_with_header(template: &str, task: &Task) -> String {
+    let title = task
+        .title
+        .as_ref()
+        .map(|title| format!("// {}\n", title))
+        .unwrap_or_default();
+    let score = task
+        .meta
+        .score
+        .map(|score| format!("// Score: {} points\n", score))
+        .unwrap_or_default();
+    title
+        + &generator::generate_limits_comment(
+            task.meta.time_limit.as_deref(),
+            task.meta.memory_limit.as_deref(),
+        )
+        + &score
+        + template
+}
+
+/// Write the statement of the task as `<task>.html` beside its source if `--fetch-problem-statement`
+/// is set, or only warn if the statement was not extracted
+fn write_statement(
+    args: &ArgMatches,
+    writer: &mut dyn FileWriter,
+    source_path: &Path,
+    task: &Task,
+) -> Result<(), Error> {
+    if !args.is_present("fetch-problem-statement") {
+        return Ok(());
+    }
+    match &task.statement_html {
+        Some(statement) => writer.write(
+            &source_path.with_extension("html"),
+            &generator::generate_statement_html(task.title.as_deref(), statement),
+        ),
+        None => {
+            warn!(
+                "Skipped {}, since the statement could not be extracted",
+                source_path.with_extension("html").display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Underlying `reqwest::Error` of the error if the request timed out
+fn timed_out_request(error: &Error) -> Option<&reqwest::Error> {
+    match error {
+        Error::Reqwest(error) | Error::Network { last: error, .. } if error.is_timeout() => {
+            Some(error)
+        }
+        _ => None,
+    }
+}
+
+/// Print which request timed out, since reqwest does not tell how long it waited
+fn report_timeout(args: &ArgMatches, config: &Config, error: &Error) {
+    if let Some(error) = timed_out_request(error) {
+        let secs = match connect_timeout(args, config) {
+            Some(connect_timeout) if error.is_connect() => connect_timeout,
+            _ => timeout(args, config),
+        }
+        .as_secs();
+        let url = error
+            .url()
+            .map(Url::to_string)
+            .unwrap_or_else(|| "AtCoder".to_owned());
+        error!("Request to {} timed out after {}s", url, secs);
+    }
+}
+
+/// Options of the tests of the task, which are stubs if the statement says it is interactive
+fn task_test_options(options: &TestOptions, task: &Task) -> TestOptions {
+    TestOptions {
+        interactive: options.interactive || task.meta.interactive,
+        ..*options
+    }
+}
+
+/// Append the project to `members` of the workspace, keeping the formatting and comments of its
+/// Cargo.toml, which is created if it does not exist
+fn add_to_workspace(workspace_root: &Path, project_path: &Path) -> Result<(), Error> {
+    let workspace_root = workspace_root.canonicalize()?;
+    let member = project_path
+        .canonicalize()?
+        .strip_prefix(&workspace_root)
+        .map_err(|_| {
+            Error::Invalid(format!(
+                "{} must be in the workspace {}",
+                project_path.display(),
+                workspace_root.display()
+            ))
+        })?
+        .to_string_lossy()
+        .replace('\\', "/");
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let mut manifest = if manifest_path.exists() {
+        fs::read_to_string(&manifest_path)?
+            .parse::<Document>()
+            .map_err(|error| Error::Invalid(format!("{}: {}", manifest_path.display(), error)))?
+    } else {
+        Document::new()
+    };
+    // Indexing a missing key creates an implicit table
+    let workspace = &mut manifest["workspace"];
+    if workspace["members"].is_none() {
+        workspace["members"] = toml_edit::value(Array::default());
+    }
+    let members = workspace["members"].as_array_mut().ok_or_else(|| {
+        Error::Invalid(format!(
+            "workspace.members in {} is not an array",
+            manifest_path.display()
+        ))
+    })?;
+    if members
+        .iter()
+        .any(|value| value.as_str() == Some(member.as_str()))
+    {
+        debug!("{} is already a member of the workspace", member);
+        return Ok(());
+    }
+    members
+        .push(member.as_str())
+        .map_err(|_| Error::Invalid("workspace.members must consist of strings".to_owned()))?;
+    fs::write(&manifest_path, manifest.to_string())?;
+    println!("Added {} to {}", member, manifest_path.display());
+    Ok(())
+}
+
+/// Read samples as a JSON object of task names to their samples, instead of fetching them
+///
+/// The tasks have neither titles nor limits.
+fn read_samples<R: Read>(reader: R) -> Result<IndexMap<String, Task>, Error> {
+    let samples: IndexMap<String, Vec<Sample>> = serde_json::from_reader(reader)?;
+    Ok(samples
+        .into_iter()
+        .map(|(name, samples)| {
+            (
+                name,
+                Task {
+                    samples,
+                    ..Task::default()
+                },
+            )
+        })
+        .collect())
+}
+
+/// Read samples saved in the file, whose modification time is regarded as when they were fetched
+fn read_samples_file(path: &Path) -> Result<(DateTime<Utc>, IndexMap<String, Task>), Error> {
+    let tasks = read_samples(BufReader::new(File::open(path)?))?;
+    let fetched_at = fs::metadata(path)?.modified()?.into();
+    Ok((fetched_at, tasks))
+}
+
+/// Fetch the contest and generate its project, or run the subcommand which needs AtCoder
+async fn generate(args: &ArgMatches<'_>, config: &Config) -> Result<(), Error> {
+    let root_url = Url::parse("https://atcoder.jp/")?;
+    let client = build_client(args, config)?;
+    match args.subcommand() {
+        ("submit", Some(args)) => return run_submit(args, config, &client, &root_url).await,
+        ("list", Some(args)) => return run_list(args, config, &client, &root_url).await,
+        ("update", Some(args)) => return run_update(args, config, &client, &root_url).await,
+        ("show", Some(args)) => return run_show(args, config, &client, &root_url).await,
+        ("validate", Some(args)) => return run_validate(args, config, &client, &root_url).await,
+        ("list-languages", Some(args)) => {
+            return run_list_languages(args, config, &client, &root_url).await
+        }
+        _ => {}
+    }
+
+    let (contest_id, root_url) =
+        parse_contest_arg(args.value_of("contest id").unwrap(), &root_url)?;
+    let contest_id = &contest_id;
+    let edition = match config.edition.as_deref() {
+        Some(edition) if args.occurrences_of("edition") == 0 => edition,
+        _ => args.value_of("edition").unwrap(),
+    };
+    if !generator::EDITIONS.contains(&edition) {
+        return Err(Error::Invalid(format!("Unknown edition: {}", edition)));
+    }
+    if let Some(layout) = config.layout.as_deref() {
+        if !LAYOUTS.contains(&layout) {
+            return Err(Error::Invalid(format!(
+                "Unknown layout in the config: {}",
+                layout
+            )));
+        }
+    }
+    let language_id = language_id(args)?;
+    let username = args.value_of("user").or(config.user.as_deref());
+    let retry = retry_policy(args, config);
+    if args.is_present("list-tasks") {
+        let cookies = if is_no_login(args, config) {
+            None
+        } else {
+            Some(authenticate(args, config, &client, &root_url, &retry).await?)
+        };
+        let html = fetch_tasks_page(&client, &root_url, contest_id, &cookies, &retry).await?;
+        // Plain names only, for shell scripts unlike the `list` subcommand
+        for task in parse_task_list(&html)? {
+            println!("{}", task.name.to_lowercase());
+        }
+        return Ok(());
+    }
+    let cache_dir = args
+        .value_of("cache-dir")
+        .filter(|_| !args.is_present("no-cache"))
+        .map(PathBuf::from);
+    let no_fetch = args.is_present("no-fetch");
+    // --no-fetch uses the cache however old it is
+    let ttl = if no_fetch {
+        Duration::from_secs(u64::MAX)
+    } else {
+        Duration::from_secs(value_t_or_exit!(args, "cache-ttl", u64))
+    };
+    let cached = match &cache_dir {
+        _ if args.is_present("samples-file") || args.is_present("stdin-samples") => None,
+        Some(cache_dir) if !args.is_present("refresh-cache") => {
+            SampleCache::load(cache_dir, contest_id, ttl)?
+        }
+        _ => None,
+    };
+    let (fetched_at, mut tasks) = if let Some(path) = args.value_of("samples-file") {
+        read_samples_file(Path::new(path))?
+    } else if args.is_present("stdin-samples") {
+        (Utc::now(), read_samples(io::stdin().lock())?)
+    } else if let Some(cache) = cached {
+        info!("Using cached samples of {} tasks", cache.tasks.len());
+        (cache.fetched_at, cache.tasks)
+    } else if no_fetch {
+        return Err(Error::Invalid(format!(
+            "No samples of {} are cached in --cache-dir, which --no-fetch needs",
+            contest_id
+        )));
+    } else {
+        let fetched_at = Utc::now();
+        let cookies = if is_no_login(args, config) {
+            None
+        } else {
+            Some(authenticate(args, config, &client, &root_url, &retry).await?)
+        };
+        let html = fetch_tasks_page(&client, &root_url, contest_id, &cookies, &retry).await?;
+        let progress = if args.is_present("no-progress") || !atty::is(atty::Stream::Stderr) {
+            None
+        } else {
+            Some(Arc::new(MultiProgress::new()))
+        };
+        let show_progress = progress.is_some();
+        let tasks = get_samples(
+            &html,
+            &client,
+            &root_url,
+            &cookies,
+            &retry,
+            value_or_config(args, "parallel", config.parallel),
+            progress,
+        )
+        .await?;
+        if show_progress {
+            info!("Fetched samples of {} tasks", tasks.len());
+        }
+        // An empty contest may not have started yet, so it is fetched again next time
+        if let (Some(cache_dir), false) = (&cache_dir, tasks.is_empty()) {
+            SampleCache::save(cache_dir, contest_id, &tasks)?;
+        }
+        (fetched_at, tasks)
+    };
+    if tasks.is_empty() {
+        return Err(Error::Invalid(
+            "No tasks found; contest may not have started".to_owned(),
+        ));
+    }
+    if let Some(names) = args.values_of("task") {
+        let names: Vec<_> = names.map(str::to_lowercase).collect();
+        for name in &names {
+            if !tasks.keys().any(|key| key.to_lowercase() == *name) {
+                warn!("task {} is not found in {}", name, contest_id);
+            }
+        }
+        tasks.retain(|key, _| names.contains(&key.to_lowercase()));
+        if tasks.is_empty() {
+            return Err(Error::Invalid(format!(
+                "None of the given tasks are found in {}",
+                contest_id
+            )));
+        }
+    }
+    if args.is_present("max-tasks") {
+        let max_tasks = value_t_or_exit!(args, "max-tasks", usize);
+        if tasks.len() > max_tasks {
+            let skipped: Vec<_> = tasks.drain(max_tasks..).map(|(key, _)| key).collect();
+            // stdout is only for the files of --output-format json
+            warn!("Skipped: {}", skipped.join(", "));
+        }
+    }
+    // Files, modules and arms of main.rs are all named after the prefixed names
+    let task_prefix = args.value_of("task-prefix").unwrap_or_default();
+    let tasks: IndexMap<_, _> = tasks
+        .into_iter()
+        .map(|(key, task)| (format!("{}{}", task_prefix, key), task))
+        .collect();
+
+    let root_path = project_root(args, config)?.join(contest_id);
+    // JSON output never touches the disk as well as --dry-run
+    let json_output = args.value_of("output-format") == Some("json");
+    let dry_run = args.is_present("dry-run") || json_output;
+    let skip_existing = args.is_present("skip-existing");
+    if !dry_run && !skip_existing && root_path.exists() {
+        if !args.is_present("force") {
+            return Err(Error::Invalid(format!("{} already exists", contest_id)));
+        }
+        warn!(
+            "{} already exists and will be replaced",
+            root_path.display()
+        );
+        if !args.is_present("yes") && !confirm("Continue?")? {
+            return Err(Error::Invalid("Aborted".to_owned()));
+        }
+    }
+    let mut writer: Box<dyn FileWriter> = if json_output {
+        Box::new(JsonWriter::default())
+    } else if dry_run {
+        Box::new(DryRunWriter::default())
+    } else if skip_existing {
+        Box::new(SkipExistingWriter::default())
+    } else if args.is_present("force") {
+        // The existing project is removed only after the new one is staged
+        Box::new(DiskWriter::replacing(&root_path)?)
+    } else {
+        Box::new(DiskWriter::new(&root_path)?)
+    };
+    writer.create_dir(&root_path)?;
+    // --proconio-version and --no-proconio override the dependencies in the config file as well
+    let uses_proconio_flags = args.is_present("proconio-version") || args.is_present("no-proconio");
+    let dependencies_path = match args.value_of("dependencies") {
+        None if uses_proconio_flags => None,
+        None => config.dependencies.as_deref(),
+        path => path,
+    };
+    let mut dependencies = if let Some(dependencies) = dependencies_path {
+        let mut reader = BufReader::new(File::open(dependencies)?);
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+        buf
+    } else if args.is_present("no-proconio") {
+        String::new()
+    } else {
+        generator::generate_proconio_dependency(
+            args.value_of("proconio-version")
+                .unwrap_or(generator::DEFAULT_PROCONIO_VERSION),
+        )
+    };
+    if let Some(fragment) = args.value_of("dependencies-append") {
+        if !dependencies.is_empty() && !dependencies.ends_with('\n') {
+            dependencies.push('\n');
+        }
+        dependencies.push_str(fragment);
+        // Reject broken fragments here rather than in the generated Cargo.toml
+        toml::from_str::<toml::Value>(&dependencies)?;
+    }
+    let test_options = test_options(args, config)?;
+    let no_tests = args.is_present("no-tests");
+    // Without tests, the harness is not needed either
+    let mut dev_dependencies = if no_tests {
+        String::new()
+    } else {
+        test_options.framework.dev_dependencies().to_owned()
+    };
+    let bench = args.is_present("bench");
+    if bench {
+        if !dev_dependencies.is_empty() {
+            dev_dependencies.push('\n');
+        }
+        dev_dependencies.push_str(generator::BENCH_DEV_DEPENDENCIES);
+    }
+    let optimize = args.is_present("optimize");
+    if optimize {
+        warn!("--optimize makes release builds (e.g. `cargo test --release`) slower to compile");
+    }
+    let template = if let Some(template) = args.value_of("template").or(config.template.as_deref())
+    {
+        let mut reader = BufReader::new(File::open(template)?);
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+        buf
+    } else if args.is_present("no-proconio") {
+        "use std::io::{self, Read};\n\npub fn main() {\n    let mut input = String::new();\n    io::stdin().read_to_string(&mut input).unwrap();\n}\n".to_owned()
+    } else {
+        "pub fn main() {\n}".to_owned()
+    };
+    let template = if let Some(vars) = args.values_of("template-vars") {
+        let vars = vars
+            .map(|var| {
+                let mut pair = var.splitn(2, '=');
+                match (pair.next(), pair.next()) {
+                    (Some(key), Some(value)) if !key.is_empty() => Ok((key, value)),
+                    _ => Err(Error::Invalid(format!(
+                        "--template-vars must be KEY=VALUE: {}",
+                        var
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (template, unknown) = generator::substitute_template_vars(&template, &vars);
+        for key in unknown {
+            warn!("{{{{{}}}}} in the template is left as is, since it is not given by --template-vars", key);
+        }
+        template
+    } else {
+        template
+    };
+    let shared_lib = args.is_present("shared-lib");
+    let template = if shared_lib {
+        generator::generate_shared_lib_use(contest_id) + &template
+    } else {
+        template
+    };
+    // Inner attributes must precede the items of the template
+    let template = if args.is_present("no-allow-attrs") {
+        template
+    } else {
+        generator::ALLOW_ATTRS.to_owned() + &template
+    };
+    let lib_path = if shared_lib { Some("src/lib.rs") } else { None };
+    let mut task_names: Vec<_> = tasks.keys().map(|key| key.to_lowercase()).collect();
+    task_names.sort_by(|a, b| generator::compare_task_names(a, b));
+    let metadata = ContestMetadata {
+        contest_id: contest_id.to_owned(),
+        fetched_at: Some(fetched_at),
+        tasks: task_names,
+        platform: metadata::PLATFORM.to_owned(),
+        language_id,
+        task_prefix: task_prefix.to_lowercase(),
+        scores: tasks
+            .iter()
+            .filter_map(|(key, task)| task.meta.score.map(|score| (key.to_lowercase(), score)))
+            .collect(),
+        tests: Some(TestSettings::new(&test_options)),
+    };
+    // The project is still usable without the metadata, except for the subcommands
+    if let Err(error) = writer.write(
+        &root_path.join(metadata::FILE_NAME),
+        &serde_json::to_string_pretty(&metadata)?,
+    ) {
+        warn!("Failed to write {}: {}", metadata::FILE_NAME, error);
+    }
+    if args.is_present("cargo-config") {
+        let cargo_path = root_path.join(".cargo");
+        writer.create_dir(&cargo_path)?;
+        writer.write(
+            &cargo_path.join("config.toml"),
+            &generator::generate_cargo_config(
+                Some(generator::JUDGE_TARGET),
+                generator::DEFAULT_DEV_OPT_LEVEL,
+            ),
+        )?;
+    }
+    if args.value_of("ci") == Some("github-actions") {
+        let workflows_path = root_path.join(".github").join("workflows");
+        writer.create_dir(&root_path.join(".github"))?;
+        writer.create_dir(&workflows_path)?;
+        writer.write(
+            &workflows_path.join("test.yml"),
+            &generator::generate_github_actions_workflow(contest_id, "stable"),
+        )?;
+    }
+    if args.is_present("devcontainer") {
+        let devcontainer_path = root_path.join(".devcontainer");
+        writer.create_dir(&devcontainer_path)?;
+        writer.write(
+            &devcontainer_path.join("devcontainer.json"),
+            &generator::generate_devcontainer("latest"),
+        )?;
+    }
+    if args.is_present("justfile") {
+        let tasks: Vec<_> = metadata.tasks.iter().map(String::as_str).collect();
+        writer.write(
+            &root_path.join("justfile"),
+            &generator::generate_justfile(contest_id, &tasks),
+        )?;
+    }
+    if !args.is_present("no-gitignore") {
+        writer.write(
+            &root_path.join(".gitignore"),
+            &generator::generate_gitignore(args.is_present("gitignore-lock")),
+        )?;
+    }
+    let problems: Vec<_> = tasks
+        .iter()
+        .map(|(key, task)| (key.as_str(), task))
+        .collect();
+    writer.write(
+        &root_path.join("PROBLEMS.md"),
+        &generator::generate_problems_md(contest_id, &problems),
+    )?;
+    // The tasks are moved into the loops below
+    let sample_counts: Vec<_> = tasks
+        .iter()
+        .map(|(key, task)| (key.clone(), task.samples.len()))
+        .collect();
+    if args.is_present("workspace") {
+        // Each task becomes a member crate under tasks/
+        let members: Vec<_> = metadata
+            .tasks
+            .iter()
+            .map(|task| format!("tasks/{}", generator::sanitize_module_name(task)))
+            .collect();
+        writer.write(
+            &root_path.join("Cargo.toml"),
+            &generator::generate_workspace_toml(
+                &members.iter().map(String::as_str).collect::<Vec<_>>(),
+                &format!("{}\n{}", dependencies, dev_dependencies),
+                edition,
+                optimize,
+            ),
+        )?;
+        writer.create_dir(&root_path.join("tasks"))?;
+        for (key, task) in tasks {
+            let source = source_with_header(&template, &task);
+            let task_name = generator::sanitize_module_name(&key);
+            let member_path = root_path.join("tasks").join(&task_name);
+            writer.create_dir(&member_path)?;
+            let mut cargo_toml = generator::generate_workspace_member_cargo_toml(
+                &task_name,
+                username,
+                &generator::generate_workspace_member_dependencies(&dependencies)?,
+                &generator::generate_workspace_member_dependencies(&dev_dependencies)?,
+                edition,
+            );
+            if bench {
+                cargo_toml += &generator::generate_bench_entries(&[&task_name]);
+            }
+            writer.write(&member_path.join("Cargo.toml"), &cargo_toml)?;
+            writer.create_dir(&member_path.join("src"))?;
+            writer.write(&member_path.join("src").join("main.rs"), &source)?;
+            write_statement(
+                args,
+                writer.as_mut(),
+                &member_path.join("src").join("main.rs"),
+                &task,
+            )?;
+            if bench {
+                writer.create_dir(&member_path.join("benches"))?;
+                writer.write(
+                    &member_path.join("benches").join(task_name.clone() + ".rs"),
+                    &generator::generate_bench_rs(&task_name, &task.samples),
+                )?;
+            }
+            if no_tests {
+                continue;
+            }
+            writer.create_dir(&member_path.join("tests"))?;
+            writer.write(
+                &member_path.join("tests").join("sample.rs"),
+                &generator::generate_test_cases(
+                    &task_test_options(&test_options, &task),
+                    &task_name,
+                    &task_name,
+                    &task.samples,
+                ),
+            )?;
+        }
+    } else if args.value_of("layout").or(config.layout.as_deref()) == Some("bin") {
+        // Each task becomes a binary of src/bin/, so main.rs is not needed
+        let bin_names: Vec<_> = metadata
+            .tasks
+            .iter()
+            .map(|task| generator::sanitize_module_name(task))
+            .collect();
+        let bin_paths: Vec<_> = bin_names
+            .iter()
+            .map(|name| format!("src/bin/{}.rs", name))
+            .collect();
+        let bins: Vec<_> = bin_names
+            .iter()
+            .zip(&bin_paths)
+            .map(|(name, path)| (name.as_str(), path.as_str()))
+            .collect();
+        let mut cargo_toml = generator::generate_cargo_toml_with_bins(
+            contest_id,
+            username,
+            &bins,
+            lib_path,
+            &dependencies,
+            &dev_dependencies,
+            edition,
+            optimize,
+        );
+        if bench {
+            let bench_names: Vec<_> = bin_names.iter().map(String::as_str).collect();
+            cargo_toml += &generator::generate_bench_entries(&bench_names);
+        }
+        writer.write(&root_path.join("Cargo.toml"), &cargo_toml)?;
+        let bin_path = root_path.join("src").join("bin");
+        let tests_path = root_path.join("tests");
+        let benches_path = root_path.join("benches");
+        writer.create_dir(&root_path.join("src"))?;
+        writer.create_dir(&bin_path)?;
+        if shared_lib {
+            writer.write(&root_path.join("src").join("lib.rs"), generator::SHARED_LIB)?;
+        }
+        if !no_tests {
+            writer.create_dir(&tests_path)?;
+        }
+        if bench {
+            writer.create_dir(&benches_path)?;
+        }
+        for (key, task) in tasks {
+            let bin_name = generator::sanitize_module_name(&key);
+            writer.write(
+                &bin_path.join(bin_name.clone() + ".rs"),
+                &source_with_header(&template, &task),
+            )?;
+            write_statement(
+                args,
+                writer.as_mut(),
+                &bin_path.join(bin_name.clone() + ".rs"),
+                &task,
+            )?;
+            if bench {
+                writer.write(
+                    &benches_path.join(bin_name.clone() + ".rs"),
+                    &generator::generate_bench_rs(&bin_name, &task.samples),
+                )?;
+            }
+            if no_tests {
+                continue;
+            }
+            writer.write(
+                &tests_path.join(bin_name.clone() + ".rs"),
+                &generator::generate_test_cases(
+                    &task_test_options(&test_options, &task),
+                    &bin_name,
+                    &bin_name,
+                    &task.samples,
+                ),
+            )?;
+        }
+    } else {
+        let mut cargo_toml = generator::generate_cargo_toml_with_bins(
+            contest_id,
+            username,
+            &[(contest_id, "src/main.rs")],
+            lib_path,
+            &dependencies,
+            &dev_dependencies,
+            edition,
+            optimize,
+        );
+        if bench {
+            let bench_names: Vec<_> = tasks
+                .keys()
+                .map(|key| generator::sanitize_module_name(key))
+                .collect();
+            cargo_toml += &generator::generate_bench_entries(
+                &bench_names.iter().map(String::as_str).collect::<Vec<_>>(),
+            );
+        }
+        writer.write(&root_path.join("Cargo.toml"), &cargo_toml)?;
+        let src_path = root_path.join("src");
+        let tests_path = root_path.join("tests");
+        let benches_path = root_path.join("benches");
+        writer.create_dir(&src_path)?;
+        if shared_lib {
+            writer.write(&src_path.join("lib.rs"), generator::SHARED_LIB)?;
+        }
+        if !no_tests {
+            writer.create_dir(&tests_path)?;
+        }
+        if bench {
+            writer.create_dir(&benches_path)?;
+        }
+        writer.write(
+            &src_path.join("main.rs"),
+            &generator::generate_main_rs(metadata.tasks.clone()),
+        )?;
+        for (key, task) in tasks {
+            writer.write(
+                &src_path.join(generator::sanitize_module_name(&key) + ".rs"),
+                &source_with_header(&template, &task),
+            )?;
+            write_statement(
+                args,
+                writer.as_mut(),
+                &src_path.join(generator::sanitize_module_name(&key) + ".rs"),
+                &task,
+            )?;
+            if bench {
+                // main.rs dispatches to the task by its argument
+                writer.write(
+                    &benches_path.join(generator::sanitize_module_name(&key) + ".rs"),
+                    &generator::generate_bench_rs_with_bin(
+                        contest_id,
+                        &key.to_lowercase(),
+                        &task.samples,
+                    ),
+                )?;
             }
-        });
-    join_all(samples).await.into_iter().collect()
+            if no_tests {
+                continue;
+            }
+            writer.write(
+                &tests_path.join(generator::sanitize_module_name(&key) + ".rs"),
+                &generator::generate_test_cases(
+                    &task_test_options(&test_options, &task),
+                    contest_id,
+                    &key.to_lowercase(),
+                    &task.samples,
+                ),
+            )?;
+        }
+    }
+    writer.finish()?;
+    if args.is_present("report") && !dry_run {
+        print_report(&root_path, &sample_counts, writer.bytes_written())?;
+    }
+
+    if let (Some(workspace_root), false) = (args.value_of("add-to-workspace"), dry_run) {
+        add_to_workspace(Path::new(workspace_root), &root_path)?;
+    }
+    if let (Some(command), false) = (args.value_of("post-generate"), dry_run) {
+        run_post_generate(command, &root_path)?;
+    }
+
+    Ok(())
 }
 
-async fn login(
-    url: Url,
-    client: &Client,
-    username: &str,
-    password: &str,
-) -> Result<HeaderMap, Error> {
-    let response = client.get(url.clone()).send().await?;
-    if response.status() != StatusCode::OK {
-        return Err(Error::Http(response.status()));
+/// Print a table of the tasks with the number of their samples and the sizes of their sources
+///
+/// The footer has the size of all the files written, including Cargo.toml and the tests.
+fn print_report(
+    project_path: &Path,
+    sample_counts: &[(String, usize)],
+    total_bytes: u64,
+) -> Result<(), Error> {
+    let mut table = Table::new();
+    table.set_header(vec!["Task", "Samples", "File", "Bytes"]);
+    for (task, samples) in sample_counts {
+        let path = task_source_path(project_path, task);
+        let bytes = fs::metadata(&path)?.len();
+        table.add_row(vec![
+            task.clone(),
+            samples.to_string(),
+            path.strip_prefix(project_path)
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+            bytes.to_string(),
+        ]);
     }
-    let csrf_token = get_csrf_token(&response)?;
-    let response = client
-        .post(url)
-        .headers(get_cookies(&response))
-        .form(&[
-            ("username", username),
-            ("password", password),
-            ("csrf_token", &csrf_token),
-        ])
-        .send()
-        .await?;
-    if response.status() != StatusCode::OK {
-        return Err(Error::Http(response.status()));
+    table.add_row(vec![
+        format!("Total: {} tasks", sample_counts.len()),
+        String::new(),
+        String::new(),
+        total_bytes.to_string(),
+    ]);
+    println!("{}", table);
+    Ok(())
+}
+
+/// Run the `--post-generate` command with the shell in the generated project
+///
+/// The project is already generated, so a failure of the command is only warned.
+fn run_post_generate(command: &str, project_path: &Path) -> Result<(), Error> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/c");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell.arg(command).current_dir(project_path).status()?;
+    if !status.success() {
+        warn!("--post-generate `{}` failed: {}", command, status);
     }
-    Ok(get_cookies(&response))
+    Ok(())
 }
 
-fn load_cookies<P: AsRef<Path>>(path: P) -> Result<HeaderMap, Error> {
-    let reader = BufReader::new(File::open(path)?);
-    Ok(reader
-        .lines()
-        .filter_map(|line| line.ok())
-        .filter_map(|line| HeaderValue::from_str(&line).ok())
-        .map(|value| (header::COOKIE, value))
-        .collect())
+/// Comment lines which tell how to install the completion script for the shell
+fn completion_instructions(shell: Shell) -> String {
+    let bin = crate_name!();
+    let steps = match shell {
+        Shell::Bash => format!(
+            "{0} --shell-completion bash > ~/.local/share/bash-completion/completions/{0}",
+            bin
+        ),
+        Shell::Zsh => format!(
+            "{0} --shell-completion zsh > ~/.zfunc/_{0}\n# and add `fpath+=~/.zfunc` before `compinit` in ~/.zshrc",
+            bin
+        ),
+        Shell::Fish => format!(
+            "{0} --shell-completion fish > ~/.config/fish/completions/{0}.fish",
+            bin
+        ),
+        Shell::PowerShell => format!(
+            "add `{} --shell-completion powershell | Out-String | Invoke-Expression` to $PROFILE",
+            bin
+        ),
+        Shell::Elvish => format!(
+            "{0} --shell-completion elvish > ~/.elvish/lib/{0}.elv\n# and add `use {0}` to ~/.elvish/rc.elv",
+            bin
+        ),
+    };
+    format!(
+        "# Completion script of {} for {}\n# Install: {}\n",
+        bin, shell, steps
+    )
 }
 
-fn save_cookies<P: AsRef<Path>>(cookies: &HeaderMap, path: P) -> Result<(), Error> {
-    let mut writer = BufWriter::new(OpenOptions::new().write(true).create(true).open(path)?);
-    writer.write_all(
-        cookies
-            .iter()
-            .filter_map(|(_, value)| value.to_str().ok())
-            .collect::<Vec<_>>()
-            .join("\n")
-            .as_bytes(),
-    )?;
+/// Print the completion script for the shell with the instructions on top of it
+fn print_completion(app: &mut App, shell: Shell) -> Result<(), Error> {
+    let mut script = Vec::new();
+    app.gen_completions_to(crate_name!(), shell, &mut script);
+    let script = String::from_utf8_lossy(&script);
+    let instructions = completion_instructions(shell);
+    // `#compdef` must stay on the first line for zsh
+    let script = match (shell, script.find('\n')) {
+        (Shell::Zsh, Some(index)) => {
+            format!(
+                "{}{}{}",
+                &script[..=index],
+                instructions,
+                &script[index + 1..]
+            )
+        }
+        _ => instructions + &script,
+    };
+    io::stdout().write_all(script.as_bytes())?;
     Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let args = app_from_crate!()
+    let mut app = app_from_crate!()
         .author("kbone")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Manage the config file")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("init").about("Write a commented template config file"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("submit")
+                .about("Submit the solution of the task and print the verdict")
+                .arg(
+                    Arg::with_name("contest id")
+                        .required(true)
+                        .help("Contest's id (e.g. abc001)"),
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .required(true)
+                        .help("Task's name (e.g. a)"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .short("f")
+                        .long("file")
+                        .takes_value(true)
+                        .help("Path to the source file (default: the task's file in the project)"),
+                )
+                .arg(
+                    Arg::with_name("verdict-timeout")
+                        .long("verdict-timeout")
+                        .takes_value(true)
+                        .value_name("SECS")
+                        .default_value("600")
+                        .help("Give up waiting for the verdict after this many seconds"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("Print the tasks of the contest without generating code")
+                .arg(
+                    Arg::with_name("contest id")
+                        .required(true)
+                        .help("Contest's id (e.g. abc001)"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Print the tasks as a JSON array"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list-languages")
+                .about("Print the IDs of the Rust languages which the contest accepts, for --lang")
+                .arg(
+                    Arg::with_name("contest id")
+                        .help("Contest's id (default: the one of the project in the current directory)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Print the statement of the task in the terminal")
+                .arg(
+                    Arg::with_name("contest id")
+                        .required(true)
+                        .help("Contest's id (e.g. abc001)"),
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .required(true)
+                        .help("Task's name (e.g. a)"),
+                )
+                .arg(
+                    Arg::with_name("no-ansi")
+                        .long("no-ansi")
+                        .help("Print plain Markdown without formatting, e.g. for pipes"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("update").about(
+                "Fetch samples again and replace the tests of the generated project in the current directory",
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("validate").about(
+                "Fetch samples again and compare them with the tests of the generated project in the current directory",
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("open")
+                .about("Open the task's page, or the tasks page of the contest, in the browser")
+                .arg(
+                    Arg::with_name("contest id")
+                        .help("Contest's id (default: the one of the project in the current directory)"),
+                )
+                .arg(Arg::with_name("task").help("Task's name (e.g. a)")),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Run cargo check in the generated project in the current directory")
+                .arg(
+                    Arg::with_name("task")
+                        .long("task")
+                        .takes_value(true)
+                        .help("Check only the task's binary or member (e.g. a)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("watch")
+                .about("Run the tests of the task whenever its source in the current project is saved")
+                .arg(
+                    Arg::with_name("task")
+                        .long("task")
+                        .takes_value(true)
+                        .help("Watch only the task's source (e.g. a)"),
+                )
+                .arg(
+                    Arg::with_name("release")
+                        .long("release")
+                        .help("Run the tests in release mode"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("test")
+                .about("Run the tests of the generated project in the current directory")
+                .arg(Arg::with_name("task").help("Task's name (default: all tasks)"))
+                .arg(
+                    Arg::with_name("release")
+                        .long("release")
+                        .help("Run the tests in release mode"),
+                ),
+        )
         .arg(
             Arg::with_name("contest id")
-                .required(true)
-                .help("Contest's id (e.g. abc001)"),
+                .required_unless("shell-completion")
+                .help("Contest's id (e.g. abc001) or its URL (e.g. https://atcoder.jp/contests/abc001/tasks)"),
+        )
+        .arg(
+            Arg::with_name("shell-completion")
+                .long("shell-completion")
+                .takes_value(true)
+                .value_name("SHELL")
+                .possible_values(&Shell::variants())
+                .help("Print the completion script for the shell and exit"),
         )
         .arg(
             Arg::with_name("user")
+                .global(true)
                 .short("u")
                 .long("user")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("verbose")
+                .global(true)
+                .short("v")
+                .long("verbose")
+                .help("Print diagnostic messages (RUST_LOG=debug for this crate)"),
+        )
         .arg(
             Arg::with_name("password")
+                .global(true)
                 .short("p")
                 .long("password")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("password-file")
+                .global(true)
+                .long("password-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Read the password from the first line of the file"),
+        )
         .arg(
             Arg::with_name("cookie")
+                .global(true)
                 .short("c")
                 .long("cookie")
                 .takes_value(true)
                 .help("Path to the cookie file (default: cookie.txt in the current directory)"),
         )
-        .arg(Arg::with_name("no-login").long("no-login"))
+        .arg(
+            Arg::with_name("cookie-format")
+                .global(true)
+                .long("cookie-format")
+                .takes_value(true)
+                .possible_values(&CookieFormat::NAMES)
+                .default_value("text")
+                .help("Format of the cookie file to save (both formats are loaded)"),
+        )
+        .arg(
+            Arg::with_name("keychain")
+                .global(true)
+                .long("keychain")
+                .conflicts_with("cookie")
+                .help("Store cookies in the OS keychain instead of the cookie file"),
+        )
+        .arg(
+            Arg::with_name("proxy")
+                .global(true)
+                .long("proxy")
+                .takes_value(true)
+                .value_name("URL")
+                .help("Proxy to AtCoder (default: HTTPS_PROXY or HTTP_PROXY)"),
+        )
+        .arg(
+            Arg::with_name("no-proxy")
+                .global(true)
+                .long("no-proxy")
+                .conflicts_with("proxy")
+                .help("Do not use any proxy even if HTTPS_PROXY or HTTP_PROXY is set"),
+        )
+        .arg(
+            Arg::with_name("user-agent")
+                .global(true)
+                .long("user-agent")
+                .takes_value(true)
+                .help("User-Agent of HTTP requests (default: atcoder4rust/<version>)"),
+        )
+        .arg(
+            Arg::with_name("lang")
+                .global(true)
+                .long("lang")
+                .takes_value(true)
+                .value_name("ID")
+                .help("Language ID to submit as, which is stored in the generated project (default: 4050)"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .global(true)
+                .long("timeout")
+                .takes_value(true)
+                .value_name("SECS")
+                .default_value("30")
+                .help("How long to wait for each HTTP request"),
+        )
+        .arg(
+            Arg::with_name("connect-timeout")
+                .global(true)
+                .long("connect-timeout")
+                .takes_value(true)
+                .value_name("SECS")
+                .help("How long to wait for connecting to AtCoder"),
+        )
+        .arg(
+            Arg::with_name("parallel")
+                .global(true)
+                .long("parallel")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("4")
+                .help("The maximum number of task pages fetched at the same time"),
+        )
+        .arg(
+            Arg::with_name("lock-timeout")
+                .global(true)
+                .long("lock-timeout")
+                .takes_value(true)
+                .value_name("SECS")
+                .default_value("5")
+                .help("How long to wait for the cookie file locked by another instance"),
+        )
+        .arg(Arg::with_name("no-login").global(true).long("no-login"))
+        .arg(
+            Arg::with_name("validate-session")
+                .global(true)
+                .long("validate-session")
+                .help("Check that the session in the cookie file is active, or login again"),
+        )
         .arg(
             Arg::with_name("root")
+                .global(true)
                 .short("r")
                 .long("root")
                 .takes_value(true)
@@ -216,6 +2070,28 @@ async fn main() -> Result<(), Error> {
                 .takes_value(true)
                 .help("Path to the file which is a dependency list written in Cargo.toml format"),
         )
+        .arg(
+            Arg::with_name("dependencies-append")
+                .long("dependencies-append")
+                .takes_value(true)
+                .value_name("TOML")
+                .conflicts_with("dependencies")
+                .help("Dependencies written in Cargo.toml format to append to the default ones (e.g. 'ac-library-rs = \"0.1\"')"),
+        )
+        .arg(
+            Arg::with_name("proconio-version")
+                .long("proconio-version")
+                .takes_value(true)
+                .value_name("VERSION")
+                .conflicts_with_all(&["dependencies", "no-proconio"])
+                .help("Version requirement of proconio (default: =0.3.6)"),
+        )
+        .arg(
+            Arg::with_name("no-proconio")
+                .long("no-proconio")
+                .conflicts_with("dependencies")
+                .help("Do not depend on proconio, and read stdin with std::io in the default template"),
+        )
         .arg(
             Arg::with_name("template")
                 .short("t")
@@ -223,160 +2099,427 @@ async fn main() -> Result<(), Error> {
                 .takes_value(true)
                 .help("Path to the template file for [task].rs"),
         )
-        .get_matches();
-    let contest_id = args.value_of("contest id").unwrap();
-    let username = args.value_of("user");
-    let password = args.value_of("password");
-
-    let root_url = Url::parse("https://atcoder.jp/")?;
-    let client = Client::builder().cookie_store(true).build()?;
-    let cookies: Option<HeaderMap> = {
-        // Find a local cookie file
-        let cookie_path = if let Some(path) = args.value_of("cookie") {
-            Path::new(path).to_owned()
-        } else {
-            env::current_dir()?.join("cookie.txt")
-        };
-        if cookie_path.exists() {
-            Some(load_cookies(cookie_path)?)
-        } else {
-            None
-        }
-    };
-    let cookies = if args.is_present("no-login") {
-        None
-    } else if let Some(cookies) = cookies {
-        Some(cookies)
+        .arg(
+            Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("Print the generated files instead of writing them"),
+        )
+        .arg(
+            Arg::with_name("output-format")
+                .long("output-format")
+                .takes_value(true)
+                .possible_values(&["files", "json"])
+                .default_value("files")
+                .help("Write files, or print them as a JSON array of {\"path\", \"content\"}"),
+        )
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Remove the contest's directory if it already exists"),
+        )
+        .arg(
+            Arg::with_name("skip-existing")
+                .long("skip-existing")
+                .conflicts_with("force")
+                .help("Write into the contest's directory even if it exists, skipping files which already exist"),
+        )
+        .arg(
+            Arg::with_name("yes")
+                .short("y")
+                .long("yes")
+                .help("Do not ask for confirmation before removing the directory"),
+        )
+        .arg(
+            Arg::with_name("no-gitignore")
+                .long("no-gitignore")
+                .help("Do not generate .gitignore"),
+        )
+        .arg(
+            Arg::with_name("gitignore-lock")
+                .long("gitignore-lock")
+                .conflicts_with("no-gitignore")
+                .help("Ignore Cargo.lock in the generated .gitignore"),
+        )
+        .arg(
+            Arg::with_name("layout")
+                .long("layout")
+                .takes_value(true)
+                .possible_values(&LAYOUTS)
+                .conflicts_with("workspace")
+                .help("Dispatch tasks from main.rs as modules (default), or make each task a binary in src/bin/"),
+        )
+        .arg(
+            Arg::with_name("optimize")
+                .long("optimize")
+                .help("Tune [profile.release] in the generated Cargo.toml for runtime speed (lto, codegen-units = 1, ...); this does not build anything"),
+        )
+        .arg(
+            Arg::with_name("cargo-config")
+                .long("cargo-config")
+                .help("Generate .cargo/config.toml with the judge's target and opt-level = 1 for dev"),
+        )
+        .arg(
+            Arg::with_name("shared-lib")
+                .long("shared-lib")
+                .conflicts_with("workspace")
+                .help("Generate src/lib.rs with common utilities (ModInt, gcd, UnionFind, FenwickTree), which every task imports"),
+        )
+        .arg(
+            Arg::with_name("template-vars")
+                .long("template-vars")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("KEY=VALUE")
+                .help("Replace {{KEY}} in the template with VALUE (can be repeated)"),
+        )
+        .arg(
+            Arg::with_name("no-allow-attrs")
+                .long("no-allow-attrs")
+                .help("Do not put #![allow(dead_code, unused_imports)] at the top of each task file"),
+        )
+        .arg(
+            Arg::with_name("no-tests")
+                .long("no-tests")
+                .help("Generate only the sources of the tasks, without sample tests"),
+        )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .help("Print a table of the generated tasks with their samples and file sizes"),
+        )
+        .arg(
+            Arg::with_name("no-report")
+                .long("no-report")
+                .overrides_with("report")
+                .help("Do not print the table of --report (default)"),
+        )
+        .arg(
+            Arg::with_name("post-generate")
+                .long("post-generate")
+                .takes_value(true)
+                .value_name("COMMAND")
+                .help("Run the command with the shell in the generated project (e.g. \"git init\")"),
+        )
+        .arg(
+            Arg::with_name("fetch-problem-statement")
+                .long("fetch-problem-statement")
+                .help("Save the statement of each task as <task>.html beside its source"),
+        )
+        .arg(
+            Arg::with_name("list-tasks")
+                .long("list-tasks")
+                .help("Print the lowercase names of the tasks one per line without generating anything"),
+        )
+        .arg(
+            Arg::with_name("bench")
+                .long("bench")
+                .help("Generate criterion benchmarks in benches/ which run each task with its sample inputs"),
+        )
+        .arg(
+            Arg::with_name("devcontainer")
+                .long("devcontainer")
+                .help("Generate .devcontainer/devcontainer.json for GitHub Codespaces"),
+        )
+        .arg(
+            Arg::with_name("justfile")
+                .long("justfile")
+                .help("Generate a justfile with test, check, run and submit recipes"),
+        )
+        .arg(
+            Arg::with_name("ci")
+                .long("ci")
+                .takes_value(true)
+                .possible_values(&["github-actions"])
+                .help("Generate a CI workflow which runs the tests"),
+        )
+        .arg(
+            Arg::with_name("cache-dir")
+                .long("cache-dir")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Cache fetched samples in <PATH>/<contest id>/samples.json and reuse them"),
+        )
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("Neither read nor write the cache of --cache-dir"),
+        )
+        .arg(
+            Arg::with_name("refresh-cache")
+                .long("refresh-cache")
+                .conflicts_with("no-cache")
+                .help("Fetch samples even if they are cached, and cache them again"),
+        )
+        .arg(
+            Arg::with_name("samples-file")
+                .long("samples-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Read samples from a JSON object of task names to arrays of {\"input\", \"output\"} without accessing AtCoder"),
+        )
+        .arg(
+            Arg::with_name("stdin-samples")
+                .long("stdin-samples")
+                .conflicts_with("samples-file")
+                .help("Read samples from stdin in the same format as --samples-file"),
+        )
+        .arg(
+            Arg::with_name("no-fetch")
+                .long("no-fetch")
+                .conflicts_with_all(&["no-cache", "refresh-cache"])
+                .help("Never access AtCoder; use --samples-file, --stdin-samples or the cache of --cache-dir however old it is"),
+        )
+        .arg(
+            Arg::with_name("cache-ttl")
+                .long("cache-ttl")
+                .takes_value(true)
+                .value_name("SECS")
+                .default_value("86400")
+                .help("Seconds after which cached samples are fetched again"),
+        )
+        .arg(
+            Arg::with_name("edition")
+                .long("edition")
+                .takes_value(true)
+                .default_value(generator::DEFAULT_EDITION)
+                .help("Edition of the generated crates (2015, 2018 or 2021)"),
+        )
+        .arg(
+            Arg::with_name("add-to-workspace")
+                .long("add-to-workspace")
+                .takes_value(true)
+                .value_name("WORKSPACE_ROOT")
+                .conflicts_with("workspace")
+                .help("Append the generated crate to members of the workspace's Cargo.toml, creating it if missing"),
+        )
+        .arg(
+            Arg::with_name("workspace")
+                .long("workspace")
+                .help("Generate a workspace which has each task as a member crate"),
+        )
+        .arg(
+            Arg::with_name("test-framework")
+                .global(true)
+                .long("test-framework")
+                .takes_value(true)
+                .possible_values(&TestFramework::NAMES)
+                .default_value("assert_cmd")
+                .help("Crate which the generated tests use as their harness"),
+        )
+        .arg(
+            Arg::with_name("compare-mode")
+                .global(true)
+                .long("compare-mode")
+                .takes_value(true)
+                .possible_values(&CompareMode::NAMES)
+                .default_value("exact")
+                .help("How generated tests compare the output with the expected one"),
+        )
+        .arg(
+            Arg::with_name("float-tolerance")
+                .global(true)
+                .long("float-tolerance")
+                .takes_value(true)
+                .value_name("EPS")
+                .help("Compare outputs token by token as floats with the tolerance in generated tests"),
+        )
+        .arg(
+            Arg::with_name("task")
+                .long("task")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Generate only the task (e.g. e), which can be given multiple times"),
+        )
+        .arg(
+            Arg::with_name("max-tasks")
+                .long("max-tasks")
+                .takes_value(true)
+                .value_name("N")
+                .help("Generate only the first N tasks in the table of tasks"),
+        )
+        .arg(
+            Arg::with_name("task-prefix")
+                .long("task-prefix")
+                .takes_value(true)
+                .value_name("PREFIX")
+                .help("Prepend PREFIX to the file and module name of each task (e.g. abc300_ for abc300_a.rs)"),
+        )
+        .arg(
+            Arg::with_name("interactive")
+                .global(true)
+                .long("interactive")
+                .help("Generate ignored test stubs as the tasks are interactive (detected by default)"),
+        )
+        .arg(
+            Arg::with_name("no-progress")
+                .long("no-progress")
+                .help("Do not show the progress of fetching (default when stderr is not a TTY)"),
+        )
+        .arg(
+            Arg::with_name("retries")
+                .global(true)
+                .long("retries")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("3")
+                .help("The maximum number of retries for transient HTTP failures"),
+        )
+        .arg(
+            Arg::with_name("retry-delay")
+                .global(true)
+                .long("retry-delay")
+                .takes_value(true)
+                .value_name("SECS")
+                .default_value("1")
+                .help("The delay before the first retry, which is doubled on each retry"),
+        )
+        .arg(
+            Arg::with_name("max-retry-wait")
+                .global(true)
+                .long("max-retry-wait")
+                .takes_value(true)
+                .value_name("SECS")
+                .default_value("60")
+                .help("The longest wait for Retry-After of 429 responses before retrying"),
+        );
+    let args = app.clone().get_matches();
+    if let Some(shell) = args.value_of("shell-completion") {
+        return print_completion(&mut app, shell.parse().unwrap());
+    }
+    // --verbose is a shorthand of RUST_LOG=debug for this crate
+    let filter = if global_matches(&args, "verbose").is_present("verbose") {
+        EnvFilter::new("atcoder4rust=debug")
     } else {
-        // Login interactively & save cookies
-        let username = if let Some(username) = username {
-            username.to_owned()
-        } else {
-            print!("User name: ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            buf.trim().to_owned()
-        };
-        let password = if let Some(password) = password {
-            password.to_owned()
-        } else {
-            print!("Password: ");
-            io::stdout().flush()?;
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf)?;
-            buf.trim().to_owned()
-        };
-        let cookies = login(root_url.join("login")?, &client, &username, &password).await?;
-        let succeeded = cookies
-            .get_all(header::COOKIE)
-            .iter()
-            .filter_map(|cookie| cookie.to_str().ok())
-            .any(|cookie| cookie.contains(&username));
-        if !succeeded {
-            return Err(Error::Invalid("Failed to login".to_owned()));
+        // Progress such as the cached samples and the judging status is shown by default
+        EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("warn,atcoder4rust=info"))
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .without_time()
+        .init();
+    if let ("config", Some(args)) = args.subcommand() {
+        if let ("init", Some(_)) = args.subcommand() {
+            let path = Config::init()?;
+            println!("Created {}", path.display());
         }
+        return Ok(());
+    }
+    if let ("test", Some(args)) = args.subcommand() {
+        return run_test(args);
+    }
+    if let ("open", Some(args)) = args.subcommand() {
+        return run_open(args);
+    }
+    if let ("watch", Some(args)) = args.subcommand() {
+        return run_watch(args);
+    }
+    if let ("check", Some(args)) = args.subcommand() {
+        return run_check(args);
+    }
 
-        let cookie_path = if let Some(path) = args.value_of("cookie") {
-            let path = Path::new(path);
-            let parent = path.parent().expect("--cookie must be a path to the file");
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-            path.to_owned()
-        } else {
-            env::current_dir().unwrap().join("cookie.txt")
-        };
-        save_cookies(&cookies, cookie_path)?;
+    // Options in the command line override the config file
+    let config = Config::load()?;
+    let result = generate(&args, &config).await;
+    if let Err(error) = &result {
+        report_timeout(&args, &config, error);
+    }
+    result
+}
 
-        Some(cookies)
-    };
-    let contest_url = root_url
-        .join("contests/")?
-        .join(&format!("{}/", contest_id))?
-        .join("tasks")?;
-    let response = client
-        .get(contest_url)
-        .headers(cookies.clone().unwrap_or_default())
-        .send()
-        .await?;
-    if response.status() != StatusCode::OK {
-        return Err(Error::Http(response.status()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App<'static, 'static> {
+        App::new("atcoder4rust")
+            .arg(
+                Arg::with_name("proxy")
+                    .global(true)
+                    .long("proxy")
+                    .takes_value(true),
+            )
+            .subcommand(SubCommand::with_name("submit"))
     }
-    let html = response.text().await?;
-    let samples = get_samples(&html, &client, &root_url, &cookies).await?;
 
-    let root_path = if let Some(root_path) = args.value_of("root") {
-        Path::new(root_path).to_owned()
-    } else {
-        env::current_dir()?
+    #[test]
+    fn global_arg_is_found_before_and_after_the_subcommand() {
+        for argv in &[
+            ["atcoder4rust", "--proxy", "http://proxy:8080", "submit"],
+            ["atcoder4rust", "submit", "--proxy", "http://proxy:8080"],
+        ] {
+            let args = app().get_matches_from(argv);
+            assert_eq!(
+                global_matches(&args, "proxy").value_of("proxy"),
+                Some("http://proxy:8080"),
+                "{:?}",
+                argv
+            );
+        }
+        let args = app().get_matches_from(&["atcoder4rust", "submit"]);
+        assert_eq!(global_matches(&args, "proxy").value_of("proxy"), None);
     }
-    .join(contest_id);
-    if root_path.exists() {
-        return Err(Error::Invalid(format!("{} is already exists", contest_id)));
+
+    #[test]
+    fn uppercase_contest_id_is_lowercased() {
+        let root_url = Url::parse("https://atcoder.jp/").unwrap();
+        assert_eq!(
+            parse_contest_arg("ABC300", &root_url).unwrap(),
+            ("abc300".to_owned(), root_url.clone())
+        );
+        assert_eq!(
+            parse_contest_arg(
+                "https://atcoder.jp/contests/ABC300/tasks/abc300_h",
+                &root_url
+            )
+            .unwrap(),
+            ("abc300".to_owned(), root_url)
+        );
     }
-    fs::create_dir(root_path.clone())?;
-    let dependencies = if let Some(dependencies) = args.value_of("dependencies") {
-        let mut reader = BufReader::new(File::open(dependencies)?);
-        let mut buf = String::new();
-        reader.read_to_string(&mut buf)?;
-        buf
-    } else {
-        r#"proconio = { version = "=0.3.6", features = ["derive"] }"#.to_owned()
-    };
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(root_path.join("Cargo.toml"))?
-        .write_all(
-            generator::generate_cargo_toml(contest_id, username, &dependencies).as_bytes(),
-        )?;
-    let src_path = root_path.join("src");
-    let tests_path = root_path.join("tests");
-    let sample_keys: Vec<_> = samples.keys().map(|key| key.to_lowercase()).collect();
-    fs::create_dir(src_path.clone())?;
-    fs::create_dir(tests_path.clone())?;
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(src_path.join("main.rs"))?
-        .write_all(generator::generate_main_rs(sample_keys).as_bytes())?;
-    let template = if let Some(template) = args.value_of("template") {
-        let mut reader = BufReader::new(File::open(template)?);
-        let mut buf = String::new();
-        reader.read_to_string(&mut buf)?;
-        buf
-    } else {
-        "pub fn main() {\n}".to_owned()
-    };
-    stream::iter(samples)
-        .map(|(key, samples)| {
-            let src = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .open(src_path.join(key.to_lowercase() + ".rs"))
-                .and_then(|mut options| options.write_all(template.as_bytes()));
-            let tests = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .open(tests_path.join(key.to_lowercase() + ".rs"))
-                .and_then(|mut options| {
-                    options.write_all(
-                        format!(
-                            "{}",
-                            generator::generate_test_cases(
-                                contest_id,
-                                &key.to_lowercase(),
-                                &samples
-                            )
-                        )
-                        .as_bytes(),
-                    )
-                });
-            src.and(tests)
-        })
-        .collect::<Result<_, _>>()
-        .await?;
 
-    Ok(())
+    #[test]
+    fn update_keeps_the_test_options_of_the_project() {
+        let app = || {
+            App::new("update")
+                .arg(
+                    Arg::with_name("test-framework")
+                        .long("test-framework")
+                        .default_value("assert_cmd"),
+                )
+                .arg(
+                    Arg::with_name("compare-mode")
+                        .long("compare-mode")
+                        .default_value("exact"),
+                )
+                .arg(
+                    Arg::with_name("float-tolerance")
+                        .long("float-tolerance")
+                        .takes_value(true),
+                )
+                .arg(Arg::with_name("interactive").long("interactive"))
+        };
+        let metadata: ContestMetadata = serde_json::from_str(
+            r#"{
+                "contest_id": "abc001",
+                "tasks": ["a"],
+                "tests": {"framework": "cli_test_dir", "compare_mode": "trim", "float_tolerance": 1e-6}
+            }"#,
+        )
+        .unwrap();
+        let config = Config::default();
+
+        let options =
+            update_test_options(&app().get_matches_from(&["update"]), &config, &metadata).unwrap();
+        assert_eq!(options.framework, TestFramework::CliTestDir);
+        assert_eq!(options.compare_mode, CompareMode::Trim);
+        assert_eq!(options.float_tolerance, Some(1e-6));
+
+        let args = app().get_matches_from(&["update", "--test-framework", "assert_cmd"]);
+        let options = update_test_options(&args, &config, &metadata).unwrap();
+        assert_eq!(options.framework, TestFramework::AssertCmd);
+        assert_eq!(options.compare_mode, CompareMode::Trim);
+    }
 }
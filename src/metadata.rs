@@ -0,0 +1,147 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use atcoder4rust::{generator::TestOptions, Error};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Name of the metadata file in the root of a generated project
+pub const FILE_NAME: &str = ".atcoder4rust.json";
+
+/// Judge which projects are generated for, as the only value of `platform` so far
+pub const PLATFORM: &str = "atcoder";
+
+fn default_platform() -> String {
+    PLATFORM.to_owned()
+}
+
+/// Metadata of a generated project, which also marks its root
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContestMetadata {
+    pub contest_id: String,
+    /// When the samples were fetched, which is missing in projects of older versions
+    #[serde(default)]
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub tasks: Vec<String>,
+    #[serde(default = "default_platform")]
+    pub platform: String,
+    /// Language ID which `submit` uses, if it is not the default one
+    #[serde(default, rename = "lang_id", alias = "language_id")]
+    pub language_id: Option<String>,
+    /// `--task-prefix` which the names in `tasks` have, unlike the names on AtCoder
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub task_prefix: String,
+    /// Points of the tasks in `tasks` whose tables of tasks or statements tell them
+    ///
+    /// They are kept apart from `tasks` rather than as `tasks[*].score`,
+    /// since older versions read `tasks` as a list of names.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub scores: IndexMap<String, u32>,
+    /// Options of the generated tests, which is missing in projects of older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestSettings>,
+}
+
+/// Options of the generated tests which `update` keeps unless they are given again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestSettings {
+    pub framework: String,
+    pub compare_mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float_tolerance: Option<f64>,
+}
+
+impl TestSettings {
+    pub fn new(options: &TestOptions) -> Self {
+        Self {
+            framework: options.framework.name().to_owned(),
+            compare_mode: options.compare_mode.name().to_owned(),
+            float_tolerance: options.float_tolerance,
+        }
+    }
+}
+
+impl ContestMetadata {
+    /// Name of the task in the project, e.g. `abc300_a` for `a`
+    pub fn prefixed(&self, task: &str) -> String {
+        if task.starts_with(&self.task_prefix) {
+            task.to_owned()
+        } else {
+            format!("{}{}", self.task_prefix, task)
+        }
+    }
+
+    /// Name of the task on AtCoder, e.g. `a` for `abc300_a`
+    pub fn unprefixed<'a>(&self, task: &'a str) -> &'a str {
+        task.strip_prefix(self.task_prefix.as_str()).unwrap_or(task)
+    }
+
+    /// Find the project which contains `path`, and return its root and metadata
+    pub fn find(path: &Path) -> Result<(PathBuf, Self), Error> {
+        let root = path
+            .ancestors()
+            .find(|dir| dir.join(FILE_NAME).exists())
+            .ok_or_else(|| {
+                Error::Invalid(format!(
+                    "Could not find {} in {} or its parents",
+                    FILE_NAME,
+                    path.display()
+                ))
+            })?;
+        let reader = BufReader::new(File::open(root.join(FILE_NAME))?);
+        Ok((root.to_owned(), serde_json::from_reader(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_round_trips() {
+        let metadata = ContestMetadata {
+            contest_id: "abc300".to_owned(),
+            fetched_at: Some("2023-04-29T12:00:00Z".parse().unwrap()),
+            tasks: vec!["abc300_a".to_owned(), "abc300_ex".to_owned()],
+            platform: PLATFORM.to_owned(),
+            language_id: Some("5054".to_owned()),
+            task_prefix: "abc300_".to_owned(),
+            scores: vec![("abc300_a".to_owned(), 100)].into_iter().collect(),
+            tests: Some(TestSettings {
+                framework: "cli_test_dir".to_owned(),
+                compare_mode: "trim".to_owned(),
+                float_tolerance: Some(1e-6),
+            }),
+        };
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: ContestMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", metadata));
+        assert!(json.contains(r#""lang_id":"5054""#), "{}", json);
+    }
+
+    #[test]
+    fn metadata_of_older_versions_gets_the_defaults() {
+        let parsed: ContestMetadata = serde_json::from_str(
+            r#"{"contest_id": "abc001", "tasks": ["a"], "language_id": "4050"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.fetched_at, None);
+        assert_eq!(parsed.platform, PLATFORM);
+        assert_eq!(parsed.language_id.as_deref(), Some("4050"));
+        assert_eq!(parsed.task_prefix, "");
+        assert!(parsed.scores.is_empty());
+        assert!(parsed.tests.is_none());
+
+        // Empty fields are left out, so the file stays as small as before
+        let json = serde_json::to_string(&parsed).unwrap();
+        assert!(
+            !json.contains("task_prefix") && !json.contains("scores") && !json.contains("tests"),
+            "{}",
+            json
+        );
+    }
+}
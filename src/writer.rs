@@ -0,0 +1,406 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use atcoder4rust::Error;
+use serde::Serialize;
+use tempfile::TempDir;
+use tracing::{debug, info, info_span};
+
+/// Destination of the generated files
+pub trait FileWriter {
+    /// Create a directory
+    fn create_dir(&mut self, path: &Path) -> Result<(), Error>;
+    /// Write the contents to the file
+    fn write(&mut self, path: &Path, contents: &str) -> Result<(), Error>;
+    /// Called after all files are written
+    fn finish(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Total size of the files which are written, for the footer of `--report`
+    fn bytes_written(&self) -> u64;
+}
+
+/// Write files to a temporary directory, and move it to the target on `finish`
+///
+/// A killed process leaves no partial project at the target.
+pub struct DiskWriter {
+    target: PathBuf,
+    staging: Option<TempDir>,
+    /// Whether an existing target is replaced on `finish`, as `--force` does
+    replace: bool,
+    bytes_written: u64,
+}
+
+impl DiskWriter {
+    /// Stage files for `target` in a temporary directory next to it,
+    /// so that it can be renamed in the same filesystem in most cases
+    pub fn new(target: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            target: target.to_owned(),
+            staging: Some(
+                tempfile::Builder::new()
+                    .prefix(".atcoder4rust")
+                    .tempdir_in(parent_dir(target))?,
+            ),
+            replace: false,
+            bytes_written: 0,
+        })
+    }
+
+    /// Like `new`, but an existing target is replaced only when all files are staged
+    ///
+    /// If a generation fails before `finish`, the existing project is left as it is.
+    pub fn replacing(target: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            replace: true,
+            ..Self::new(target)?
+        })
+    }
+
+    /// Path in the temporary directory which corresponds to `path` in the target
+    fn staged(&self, path: &Path) -> Result<PathBuf, Error> {
+        let staging = self
+            .staging
+            .as_ref()
+            .ok_or_else(|| Error::Invalid("Files are already moved".to_owned()))?;
+        let relative = path.strip_prefix(&self.target).map_err(|_| {
+            Error::Invalid(format!(
+                "{} is not in {}",
+                path.display(),
+                self.target.display()
+            ))
+        })?;
+        Ok(staging.path().join(relative))
+    }
+}
+
+/// Directory which contains `path`, where the temporary directories are made
+fn parent_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// Move the staged directory to the target, copying it if they are on different filesystems
+fn move_staged(staging: TempDir, target: &Path) -> Result<(), Error> {
+    match fs::rename(staging.path(), target) {
+        Ok(()) => {
+            // The directory is already moved, so it must not be removed on drop
+            staging.into_path();
+        }
+        Err(error) if crosses_devices(&error) => {
+            debug!("falling back to copying {}", staging.path().display());
+            if let Err(error) = copy_dir(staging.path(), target) {
+                // A half copied target must not be left
+                if target.exists() {
+                    fs::remove_dir_all(target)?;
+                }
+                return Err(error.into());
+            }
+            staging.close()?;
+        }
+        // The staged files are removed on drop of `staging`
+        Err(error) => return Err(error.into()),
+    }
+    Ok(())
+}
+
+/// Whether `rename` failed because the paths are on different filesystems
+///
+/// Only then copying can stand in for renaming; other errors such as permissions have to be reported.
+pub fn crosses_devices(error: &io::Error) -> bool {
+    // EXDEV on Unix, ERROR_NOT_SAME_DEVICE on Windows
+    let code = if cfg!(windows) { 17 } else { 18 };
+    error.raw_os_error() == Some(code)
+}
+
+/// Copy the directory recursively
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to)?;
+        } else {
+            fs::copy(entry.path(), to)?;
+        }
+    }
+    Ok(())
+}
+
+impl FileWriter for DiskWriter {
+    fn create_dir(&mut self, path: &Path) -> Result<(), Error> {
+        debug!(path = %path.display(), "create directory");
+        let path = self.staged(path)?;
+        // The temporary directory itself stands for the target
+        if !path.exists() {
+            fs::create_dir(path)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, contents: &str) -> Result<(), Error> {
+        let span = info_span!("write", path = %path.display());
+        let _enter = span.enter();
+        let path = self.staged(path)?;
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(path)?
+            .write_all(contents.as_bytes())?;
+        debug!(bytes = contents.len(), "written");
+        self.bytes_written += contents.len() as u64;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let staging = match self.staging.take() {
+            Some(staging) => staging,
+            None => return Ok(()),
+        };
+        if !(self.replace && self.target.exists()) {
+            return move_staged(staging, &self.target);
+        }
+        // The existing project is moved aside rather than removed, to be restored on failure
+        let aside = tempfile::Builder::new()
+            .prefix(".atcoder4rust-old")
+            .tempdir_in(parent_dir(&self.target))?;
+        let old = aside.path().join("project");
+        fs::rename(&self.target, &old)?;
+        if let Err(error) = move_staged(staging, &self.target) {
+            fs::rename(&old, &self.target)?;
+            return Err(error);
+        }
+        // The old project is removed with the directory it was moved to
+        aside.close()?;
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+/// Write files directly to a directory which may already exist, leaving existing files as they are
+///
+/// This resumes a generation which was interrupted, so it is not atomic unlike `DiskWriter`.
+#[derive(Default)]
+pub struct SkipExistingWriter {
+    /// Existing files are not counted, since they are left as they are
+    bytes_written: u64,
+}
+
+impl FileWriter for SkipExistingWriter {
+    fn create_dir(&mut self, path: &Path) -> Result<(), Error> {
+        if !path.exists() {
+            fs::create_dir_all(path)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, contents: &str) -> Result<(), Error> {
+        if path.exists() {
+            info!(path = %path.display(), "skipped existing file");
+            return Ok(());
+        }
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?
+            .write_all(contents.as_bytes())?;
+        debug!(path = %path.display(), bytes = contents.len(), "written");
+        self.bytes_written += contents.len() as u64;
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+/// Keep files in memory and print them to stdout instead of writing to the disk
+#[derive(Default)]
+pub struct DryRunWriter {
+    files: Vec<(PathBuf, String)>,
+}
+
+impl FileWriter for DryRunWriter {
+    fn create_dir(&mut self, _path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, contents: &str) -> Result<(), Error> {
+        self.files.push((path.to_owned(), contents.to_owned()));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        for (path, contents) in &self.files {
+            println!("=== {} ===", path.display());
+            println!("{}", contents);
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.files
+            .iter()
+            .map(|(_, contents)| contents.len() as u64)
+            .sum()
+    }
+}
+
+/// A file which `JsonWriter` prints
+#[derive(Serialize)]
+struct JsonFile {
+    path: PathBuf,
+    content: String,
+}
+
+/// Keep files in memory and print them to stdout as a JSON array instead of writing to the disk
+#[derive(Default)]
+pub struct JsonWriter {
+    files: Vec<JsonFile>,
+}
+
+impl FileWriter for JsonWriter {
+    fn create_dir(&mut self, _path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, contents: &str) -> Result<(), Error> {
+        self.files.push(JsonFile {
+            path: path.to_owned(),
+            content: contents.to_owned(),
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        println!("{}", serde_json::to_string(&self.files)?);
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.files
+            .iter()
+            .map(|file| file.content.len() as u64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finish_moves_the_staged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("abc001");
+        let mut writer = DiskWriter::new(&target).unwrap();
+        writer.create_dir(&target).unwrap();
+        writer.create_dir(&target.join("src")).unwrap();
+        writer
+            .write(&target.join("src").join("a.rs"), "fn main() {}\n")
+            .unwrap();
+        writer
+            .write(&target.join("Cargo.toml"), "[package]\n")
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(target.join("src").join("a.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        assert_eq!(writer.bytes_written(), 23);
+        // No temporary directory is left next to the target
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn only_exdev_crosses_devices() {
+        let exdev = if cfg!(windows) { 17 } else { 18 };
+        assert!(crosses_devices(&io::Error::from_raw_os_error(exdev)));
+        assert!(!crosses_devices(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+    }
+
+    #[test]
+    fn failed_rename_is_reported_instead_of_copying() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("abc001");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("a.rs"), "solved").unwrap();
+
+        let mut writer = DiskWriter::new(&target).unwrap();
+        writer.create_dir(&target).unwrap();
+        writer.write(&target.join("a.rs"), "template").unwrap();
+        writer.write(&target.join("b.rs"), "template").unwrap();
+        match writer.finish() {
+            Err(Error::Io(error)) => assert!(!crosses_devices(&error)),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // The existing project is left as it is, and the staged files are removed
+        assert_eq!(fs::read_to_string(target.join("a.rs")).unwrap(), "solved");
+        assert!(!target.join("b.rs").exists());
+        drop(writer);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn existing_target_is_replaced_only_on_finish() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("abc001");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("a.rs"), "solved").unwrap();
+
+        let mut writer = DiskWriter::replacing(&target).unwrap();
+        writer.create_dir(&target).unwrap();
+        writer.write(&target.join("b.rs"), "template").unwrap();
+        // A generation which fails before finish leaves the existing project
+        assert_eq!(fs::read_to_string(target.join("a.rs")).unwrap(), "solved");
+        writer.finish().unwrap();
+
+        assert!(!target.join("a.rs").exists());
+        assert_eq!(fs::read_to_string(target.join("b.rs")).unwrap(), "template");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn dropped_writer_leaves_the_existing_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("abc001");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("a.rs"), "solved").unwrap();
+
+        let mut writer = DiskWriter::replacing(&target).unwrap();
+        writer.create_dir(&target).unwrap();
+        writer.write(&target.join("a.rs"), "template").unwrap();
+        drop(writer);
+
+        assert_eq!(fs::read_to_string(target.join("a.rs")).unwrap(), "solved");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn skipped_files_are_not_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "solved").unwrap();
+
+        let mut writer = SkipExistingWriter::default();
+        writer.write(&dir.path().join("a.rs"), "template").unwrap();
+        writer.write(&dir.path().join("b.rs"), "template").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.rs")).unwrap(),
+            "solved"
+        );
+        assert_eq!(writer.bytes_written(), 8);
+    }
+}
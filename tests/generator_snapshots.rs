@@ -0,0 +1,49 @@
+//! Compares the files which the generator writes with the snapshots in `tests/fixtures/generated`
+//!
+//! When the output changes on purpose, update the snapshot together with the generator.
+
+use atcoder4rust::{
+    generator::{self, CompareMode, TestFramework, TestOptions},
+    Sample,
+};
+
+#[test]
+fn cargo_toml() {
+    let cargo_toml = generator::generate_cargo_toml(
+        "abc001",
+        Some("kbone <kbone@example.com>"),
+        &generator::generate_proconio_dependency(generator::DEFAULT_PROCONIO_VERSION),
+        TestFramework::AssertCmd.dev_dependencies(),
+        "2021",
+        false,
+    );
+
+    assert_eq!(
+        cargo_toml,
+        include_str!("fixtures/generated/cargo_toml.snap")
+    );
+}
+
+#[test]
+fn test_module() {
+    let options = TestOptions {
+        framework: TestFramework::AssertCmd,
+        compare_mode: CompareMode::Exact,
+        float_tolerance: None,
+        interactive: false,
+    };
+    let samples = [
+        Sample {
+            input: "1 2\n".to_owned(),
+            output: "3\n".to_owned(),
+        },
+        Sample {
+            input: "10 20\n".to_owned(),
+            output: "30\n".to_owned(),
+        },
+    ];
+
+    let tests = generator::generate_test_cases(&options, "abc001", "a", &samples);
+
+    assert_eq!(tests, include_str!("fixtures/generated/tests_a.rs.snap"));
+}
@@ -0,0 +1,504 @@
+//! Runs the command against a mock server which mimics the pages of AtCoder
+//!
+//! The contest is given as a URL on the mock server, so that it is used as the root of AtCoder.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use atcoder4rust::Error;
+use reqwest::{Client, Url};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{body_string_contains, method, path},
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+const PASSWORD: &str = "secret";
+
+/// `REVEL_SESSION` whose CSRF token is `token`, percent-encoded as AtCoder does
+const SESSION_COOKIE: &str = "REVEL_SESSION=%00csrf_token%3Atoken%00; Path=/";
+
+const LOGIN_PAGE: &str = r#"<html><body>
+<div class="alert alert-danger">Username or Password is incorrect.</div>
+<form method="POST"><input name="username"><input type="password" name="password"></form>
+</body></html>"#;
+
+const TASKS_PAGE: &str = r#"<html><body><table><tbody>
+<tr><td><a href="/contests/abc001/tasks/abc001_a">A</a></td><td>Add</td><td>100</td></tr>
+<tr><td><a href="/contests/abc001/tasks/abc001_b">B</a></td><td>Multiply</td><td>200</td></tr>
+</tbody></table></body></html>"#;
+
+/// Task page whose statement has two pairs of samples
+fn task_page(title: &str, samples: [(&str, &str); 2]) -> String {
+    let parts: String = samples
+        .iter()
+        .enumerate()
+        .map(|(index, (input, output))| {
+            format!(
+                r#"<div class="part"><section><h3>入力例 {index}</h3><pre>{input}</pre></section></div>
+<div class="part"><section><h3>出力例 {index}</h3><pre>{output}</pre></section></div>
+"#,
+                index = index + 1,
+                input = input,
+                output = output
+            )
+        })
+        .collect();
+    format!(
+        r#"<html><body>
+<div id="task-statement"><h2>{title}</h2><span class="lang"><span class="lang-ja">
+{parts}</span></span></div>
+</body></html>"#,
+        title = title,
+        parts = parts
+    )
+}
+
+/// Mount the login endpoint, the contest `abc001`, which has the tasks A and B,
+/// and the contest `abc002`, which has not started yet
+async fn mount_atcoder(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", SESSION_COOKIE)
+                .set_body_string(LOGIN_PAGE),
+        )
+        .mount(server)
+        .await;
+    // AtCoder redirects to the home page on success, or to the login page on failure
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .and(body_string_contains(&format!("password={}", PASSWORD)))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/home"))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .and(body_string_contains("password=wrong"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/login"))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/home"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", "REVEL_SESSION=logged_in; Path=/")
+                .set_body_string("<html></html>"),
+        )
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc001/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(TASKS_PAGE))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc001/tasks/abc001_a"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(task_page(
+            "A - Add",
+            [("1 2\n", "3\n"), ("10 20\n", "30\n")],
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc001/tasks/abc001_b"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(task_page(
+            "B - Multiply",
+            [("2 3\n", "6\n"), ("4 5\n", "20\n")],
+        )))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc002/tasks"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/empty_tasks.html")),
+        )
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc999/tasks"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(server)
+        .await;
+}
+
+/// Mount the contest `abc300`, whose tasks page is saved in `tests/fixtures`
+async fn mount_abc300(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/contests/abc300/tasks"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/abc300_tasks.html")),
+        )
+        .mount(server)
+        .await;
+    for task in &["a", "b", "c", "d", "e", "f", "g", "h"] {
+        Mock::given(method("GET"))
+            .and(path(
+                format!("/contests/abc300/tasks/abc300_{}", task).as_str(),
+            ))
+            .respond_with(
+                // Like the real contest, the score is only in the statement
+                ResponseTemplate::new(200).set_body_string(
+                    task_page(task, [("1\n", "1\n"), ("2\n", "2\n")]).replace(
+                        r#"<div id="task-statement">"#,
+                        r#"<div id="task-statement"><p>配点 : <var>100</var> 点</p>"#,
+                    ),
+                ),
+            )
+            .mount(server)
+            .await;
+    }
+}
+
+/// Run the command in `dir`, isolated from the config file of the user
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_atcoder4rust"))
+        .args(&["--root", dir.to_str().unwrap()])
+        .args(&["--cookie", dir.join("cookie.txt").to_str().unwrap()])
+        .args(args)
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env_remove("ATCODER_USER")
+        .env_remove("ATCODER_PASS")
+        .output()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn login_and_generate() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc001/tasks", server.uri());
+
+    let output = run(
+        dir.path(),
+        &["--user", "kbone", "--password", PASSWORD, &contest_url],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let cookies = fs::read_to_string(dir.path().join("cookie.txt")).unwrap();
+    assert!(cookies.contains("REVEL_SESSION="));
+    let project = dir.path().join("abc001");
+    for file in &[
+        "Cargo.toml",
+        ".atcoder4rust.json",
+        "src/main.rs",
+        "src/a.rs",
+        "src/b.rs",
+        "tests/a.rs",
+        "tests/b.rs",
+    ] {
+        assert!(project.join(file).exists(), "{} is not generated", file);
+    }
+    let cargo_toml = fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"name = "abc001""#));
+}
+
+#[tokio::test]
+async fn uppercase_contest_id_is_normalized() {
+    let server = MockServer::start().await;
+    mount_abc300(&server).await;
+    let dir = TempDir::new().unwrap();
+    // Only the lowercase pages are mounted
+    let contest_url = format!("{}/contests/ABC300/tasks", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Contest ID normalized: ABC300 → abc300"),
+        "{}",
+        stderr
+    );
+    let project = dir.path().join("abc300");
+    let cargo_toml = fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"name = "abc300""#));
+    let metadata = fs::read_to_string(project.join(".atcoder4rust.json")).unwrap();
+    assert!(
+        metadata.contains(r#""contest_id": "abc300""#),
+        "{}",
+        metadata
+    );
+    for file in &["src/a.rs", "src/ex.rs", "tests/ex.rs"] {
+        assert!(project.join(file).exists(), "{} is not generated", file);
+    }
+    let main_rs = fs::read_to_string(project.join("src/main.rs")).unwrap();
+    assert!(main_rs.find("mod g;").unwrap() < main_rs.find("mod ex;").unwrap());
+    let entries: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert!(
+        !entries.iter().any(|name| name == "ABC300"),
+        "{:?}",
+        entries
+    );
+}
+
+#[tokio::test]
+async fn wrong_password_fails_without_generating() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc001", server.uri());
+
+    let output = run(
+        dir.path(),
+        &["--user", "kbone", "--password", "wrong", &contest_url],
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("wrong credentials"), "{}", stderr);
+    assert!(!dir.path().join("cookie.txt").exists());
+    assert!(!dir.path().join("abc001").exists());
+}
+
+#[tokio::test]
+async fn unknown_contest_is_not_found() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc999", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("404"), "{}", stderr);
+    assert!(!dir.path().join("abc999").exists());
+}
+
+#[tokio::test]
+async fn contest_without_tasks_fails_without_generating() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc002", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No tasks found"), "{}", stderr);
+    assert!(!dir.path().join("abc002").exists());
+}
+
+#[tokio::test]
+async fn missing_task_page_fails_without_generating() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    // The task page is not mounted, so the mock server responds 404
+    Mock::given(method("GET"))
+        .and(path("/contests/abc003/tasks"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<html><body><table><tbody>
+<tr><td><a href="/contests/abc003/tasks/abc003_a">A</a></td><td>Missing</td></tr>
+</tbody></table></body></html>"#,
+        ))
+        .mount(&server)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc003", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("404"), "{}", stderr);
+    assert!(!dir.path().join("abc003").exists());
+}
+
+#[tokio::test]
+async fn two_tasks_with_two_samples_each() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc001", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let project = dir.path().join("abc001");
+    for (task, samples) in &[
+        ("a", [("1 2\n", "3\n"), ("10 20\n", "30\n")]),
+        ("b", [("2 3\n", "6\n"), ("4 5\n", "20\n")]),
+    ] {
+        let tests = fs::read_to_string(project.join("tests").join(format!("{}.rs", task))).unwrap();
+        assert!(tests.contains("fn sample_1()"));
+        assert!(tests.contains("fn sample_2()"));
+        assert!(!tests.contains("fn sample_3()"));
+        for (input, output) in samples {
+            assert!(
+                tests.contains(input),
+                "{} is not in the tests of {}",
+                input,
+                task
+            );
+            assert!(
+                tests.contains(output),
+                "{} is not in the tests of {}",
+                output,
+                task
+            );
+        }
+    }
+    let source = fs::read_to_string(project.join("src").join("a.rs")).unwrap();
+    assert!(source.starts_with("// A - Add\n"), "{}", source);
+    let main = fs::read_to_string(project.join("src").join("main.rs")).unwrap();
+    assert!(
+        main.contains("mod a;") && main.contains("mod b;"),
+        "{}",
+        main
+    );
+}
+
+#[tokio::test]
+async fn scores_are_taken_from_the_tasks_table() {
+    let server = MockServer::start().await;
+    mount_atcoder(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc001", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let project = dir.path().join("abc001");
+    let source = fs::read_to_string(project.join("src").join("b.rs")).unwrap();
+    assert!(source.contains("// Score: 200 points\n"), "{}", source);
+    let problems = fs::read_to_string(project.join("PROBLEMS.md")).unwrap();
+    assert!(problems.contains("| A | A - Add | 100 |\n"), "{}", problems);
+    assert!(
+        problems.contains("| B | B - Multiply | 200 |\n"),
+        "{}",
+        problems
+    );
+}
+
+#[tokio::test]
+async fn scores_are_taken_from_the_statements_without_the_column() {
+    let server = MockServer::start().await;
+    mount_abc300(&server).await;
+    let dir = TempDir::new().unwrap();
+    let contest_url = format!("{}/contests/abc300", server.uri());
+
+    let output = run(dir.path(), &["--no-login", &contest_url]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let project = dir.path().join("abc300");
+    let source = fs::read_to_string(project.join("src").join("ex.rs")).unwrap();
+    assert!(source.contains("// Score: 100 points\n"), "{}", source);
+    let metadata = fs::read_to_string(project.join(".atcoder4rust.json")).unwrap();
+    assert!(metadata.contains(r#""ex": 100"#), "{}", metadata);
+}
+
+#[tokio::test]
+async fn verdict_which_stays_waiting_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/contests/abc001/submissions/me"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<table><tbody>
+<tr><td>A - Add</td><td class="text-center"><span class="label">WJ</span></td></tr>
+</tbody></table>"#,
+        ))
+        .mount(&server)
+        .await;
+    let url = Url::parse(&format!("{}/contests/abc001/submissions/me", server.uri())).unwrap();
+
+    let result = atcoder4rust::wait_for_verdict(
+        &Client::new(),
+        &url,
+        &Default::default(),
+        &Default::default(),
+        Duration::from_secs(0),
+    )
+    .await;
+
+    match result {
+        Err(Error::Invalid(message)) => assert!(message.contains("WJ"), "{}", message),
+        result => panic!("{:?}", result),
+    }
+}
+
+/// Task page which is sent after a delay, recording when each request arrived
+#[derive(Clone, Default)]
+struct SlowTaskPage {
+    arrivals: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl SlowTaskPage {
+    const DELAY: Duration = Duration::from_millis(200);
+
+    /// The largest number of requests which arrived while another was being responded to
+    fn max_in_flight(&self) -> usize {
+        let arrivals = self.arrivals.lock().unwrap();
+        arrivals
+            .iter()
+            .map(|&end| {
+                arrivals
+                    .iter()
+                    .filter(|&&start| start <= end && end - start < Self::DELAY)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Respond for SlowTaskPage {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        self.arrivals.lock().unwrap().push(Instant::now());
+        ResponseTemplate::new(200)
+            .set_body_string(task_page("Slow", [("1\n", "1\n"), ("2\n", "2\n")]))
+            .set_delay(Self::DELAY)
+    }
+}
+
+#[tokio::test]
+async fn parallel_caps_the_requests_in_flight() {
+    let server = MockServer::start().await;
+    let responder = SlowTaskPage::default();
+    let mut tasks_page = String::from("<table><tbody>\n");
+    for index in 1..=6 {
+        let task_path = format!("/contests/abc009/tasks/abc009_{}", index);
+        tasks_page.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td></tr>\n",
+            task_path, index
+        ));
+        Mock::given(method("GET"))
+            .and(path(task_path.as_str()))
+            .respond_with(responder.clone())
+            .mount(&server)
+            .await;
+    }
+    tasks_page.push_str("</tbody></table>");
+    let root_url = Url::parse(&server.uri()).unwrap();
+
+    let tasks = atcoder4rust::get_samples(
+        &tasks_page,
+        &Client::new(),
+        &root_url,
+        &None,
+        &Default::default(),
+        2,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        tasks.keys().collect::<Vec<_>>(),
+        ["1", "2", "3", "4", "5", "6"]
+    );
+    assert_eq!(responder.arrivals.lock().unwrap().len(), 6);
+    assert_eq!(responder.max_in_flight(), 2);
+}
@@ -0,0 +1,158 @@
+//! Parses saved task pages in `tests/fixtures`, one for each layout of samples
+
+use atcoder4rust::{
+    parse_contest_samples, parse_problem_meta, parse_samples, parse_task_list, ContestType, Error,
+    Sample,
+};
+
+/// Samples as pairs of (input, output), to compare them at once
+fn pairs(samples: &[Sample]) -> Vec<(&str, &str)> {
+    samples
+        .iter()
+        .map(|sample| (sample.input.as_str(), sample.output.as_str()))
+        .collect()
+}
+
+#[test]
+fn abc_with_two_samples() {
+    let samples = parse_samples(include_str!("fixtures/abc_two_samples.html")).unwrap();
+
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].input, "1 2\n");
+    assert_eq!(samples[0].output, "3\n");
+    assert_eq!(samples[1].input, "100 100\n");
+    assert_eq!(samples[1].output, "200\n");
+}
+
+#[test]
+fn english_page() {
+    let samples = parse_samples(include_str!("fixtures/abc_english.html")).unwrap();
+
+    assert_eq!(pairs(&samples), [("1 2\n", "3\n"), ("100 100\n", "200\n")]);
+}
+
+#[test]
+fn bilingual_page_is_not_duplicated() {
+    let html = include_str!("fixtures/abc_bilingual.html");
+    // The same page is served in either locale, and only `lang` differs
+    for html in &[
+        html.to_owned(),
+        html.replace(r#"<html lang="ja">"#, r#"<html lang="en">"#),
+    ] {
+        let samples = parse_samples(html).unwrap();
+
+        assert_eq!(pairs(&samples), [("1 2\n", "3\n"), ("100 100\n", "200\n")]);
+    }
+}
+
+#[test]
+fn three_samples_with_multiple_lines() {
+    let samples = parse_samples(include_str!("fixtures/three_samples.html")).unwrap();
+
+    assert_eq!(
+        pairs(&samples),
+        [
+            ("3\n3 1 2\n", "1 2 3\n"),
+            ("1\n5\n", "5\n"),
+            ("4\n2 2 1 1\n", "1 1 2 2\n"),
+        ]
+    );
+}
+
+#[test]
+fn newer_layout_with_io_style_divs() {
+    let samples = parse_samples(include_str!("fixtures/io_style.html")).unwrap();
+
+    assert_eq!(pairs(&samples), [("5\n", "25\n"), ("12\n", "144\n")]);
+}
+
+#[test]
+fn newer_layout_with_bare_sections() {
+    let samples = parse_samples(include_str!("fixtures/section_pre.html")).unwrap();
+
+    assert_eq!(pairs(&samples), [("atcoder\n", "redocta\n")]);
+}
+
+#[test]
+fn newer_layout_with_sections_without_headings() {
+    let samples =
+        parse_samples(include_str!("fixtures/section_pre_without_headings.html")).unwrap();
+
+    // The format of the input in a section with a heading is not a sample
+    assert_eq!(
+        pairs(&samples),
+        [("atcoder\n", "redocta\n"), ("rust\n", "tsur\n")]
+    );
+}
+
+#[test]
+fn old_arc_with_samples_side_by_side_in_a_table() {
+    let samples = parse_contest_samples(
+        ContestType::Arc,
+        include_str!("fixtures/arc_legacy_table.html"),
+    )
+    .unwrap();
+
+    assert_eq!(pairs(&samples), [("1\n", "2\n"), ("21\n", "42\n")]);
+}
+
+#[test]
+fn pre_2013_page_with_a_header_and_a_sample_in_each_row() {
+    let samples = parse_samples(include_str!("fixtures/pre2013_table.html")).unwrap();
+
+    // The sample may not be wrapped in `<pre>`
+    assert_eq!(
+        pairs(&samples),
+        [("9\n1 3 2 1\n", "2\n"), ("4\n4 4 4 4\n", "4")]
+    );
+}
+
+#[test]
+fn page_without_samples_is_a_parse_error() {
+    match parse_samples(include_str!("fixtures/no_samples.html")) {
+        Err(Error::Parse(_)) => {}
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn invalid_html_is_an_error_rather_than_a_panic() {
+    for text in &[
+        "",
+        "<<<pre>\0</h3>",
+        "</div></div><h3>入力例 1",
+        "\u{feff}<pre",
+    ] {
+        assert!(parse_samples(text).is_err(), "{:?}", text);
+    }
+}
+
+#[test]
+fn contest_which_has_not_started_has_no_tasks() {
+    let tasks = parse_task_list(include_str!("fixtures/empty_tasks.html")).unwrap();
+
+    assert!(tasks.is_empty());
+}
+
+#[test]
+fn abc300_tasks_whose_last_one_is_ex() {
+    let tasks = parse_task_list(include_str!("fixtures/abc300_tasks.html")).unwrap();
+
+    let names: Vec<_> = tasks.iter().map(|task| task.name.as_str()).collect();
+    assert_eq!(names, ["A", "B", "C", "D", "E", "F", "G", "Ex"]);
+    assert_eq!(tasks[0].url, "/contests/abc300/tasks/abc300_a");
+    // The task id keeps the letter H, unlike the name
+    assert_eq!(tasks[7].url, "/contests/abc300/tasks/abc300_h");
+    // The cell after the task name is the time limit, which is not a score
+    assert!(tasks.iter().all(|task| task.score.is_none()));
+}
+
+#[test]
+fn score_in_the_statement() {
+    let meta = parse_problem_meta(include_str!("fixtures/abc_two_samples.html")).unwrap();
+    assert_eq!(meta.score, Some(100));
+    let meta = parse_problem_meta(include_str!("fixtures/abc_english.html")).unwrap();
+    assert_eq!(meta.score, Some(100));
+    let meta = parse_problem_meta(include_str!("fixtures/no_samples.html")).unwrap();
+    assert_eq!(meta.score, None);
+}
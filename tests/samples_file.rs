@@ -0,0 +1,115 @@
+//! Generates projects from samples in a file, without accessing AtCoder
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+use tempfile::TempDir;
+
+/// Samples of the tasks A and B of `abc001`
+const SAMPLES: &str = r#"{
+  "A": [{"input": "1 2\n", "output": "3\n"}],
+  "B": [{"input": "2 3\n", "output": "6\n"}]
+}"#;
+
+/// Run the command in `dir` with `samples.json` written there, isolated from the config file of the user
+fn run(dir: &Path, args: &[&str]) -> Output {
+    let samples = dir.join("samples.json");
+    fs::write(&samples, SAMPLES).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_atcoder4rust"))
+        .args(&["--root", dir.to_str().unwrap()])
+        .args(&["--samples-file", samples.to_str().unwrap()])
+        .args(args)
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn json_output_with_skipped_tasks_is_a_single_document() {
+    let dir = TempDir::new().unwrap();
+
+    let output = run(
+        dir.path(),
+        &["--output-format", "json", "--max-tasks", "1", "abc001"],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let files: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let paths: Vec<_> = files
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["path"].as_str().unwrap().to_owned())
+        .collect();
+    assert!(
+        paths.iter().any(|path| path.ends_with("a.rs")),
+        "{:?}",
+        paths
+    );
+    assert!(
+        !paths.iter().any(|path| path.ends_with("b.rs")),
+        "{:?}",
+        paths
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Skipped: B"), "{}", stderr);
+    assert!(!dir.path().join("abc001").exists());
+}
+
+#[test]
+fn second_generation_without_force_fails_and_keeps_the_project() {
+    let dir = TempDir::new().unwrap();
+    let output = run(dir.path(), &["abc001"]);
+    assert!(output.status.success(), "{:?}", output);
+    let project = dir.path().join("abc001");
+    let solution = project.join("src").join("a.rs");
+    fs::write(&solution, "// solved\n").unwrap();
+
+    let output = run(dir.path(), &["abc001"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("abc001 already exists"), "{}", stderr);
+    assert_eq!(fs::read_to_string(&solution).unwrap(), "// solved\n");
+    assert!(project.join("tests").join("b.rs").exists());
+}
+
+/// Total size of the files under `dir`
+fn total_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                total_size(&entry.path())
+            } else {
+                entry.metadata().unwrap().len()
+            }
+        })
+        .sum()
+}
+
+#[test]
+fn report_total_counts_every_file_written() {
+    let dir = TempDir::new().unwrap();
+
+    let output = run(dir.path(), &["--report", "abc001"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let footer = stdout
+        .lines()
+        .find(|line| line.contains("Total: 2 tasks"))
+        .unwrap_or_else(|| panic!("{}", stdout));
+    let total = total_size(&dir.path().join("abc001"));
+    assert!(
+        footer.contains(&format!(" {} ", total)),
+        "{} bytes are written\n{}",
+        total,
+        stdout
+    );
+}